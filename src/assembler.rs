@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 
+use crate::variant::CpuVariant;
+
 #[derive(Debug, PartialEq, Clone, Copy, Hash, Eq, PartialOrd, Ord)]
 pub enum AddressingMode {
     Implied,
@@ -14,6 +16,17 @@ pub enum AddressingMode {
     Indirect,
     IndirectX,
     IndirectY,
+    ZeroPageIndirect,        // 65C02 (zp)
+    AbsoluteIndexedIndirect, // 65C02 JMP (abs,X)
+    ZeroPageRelative,        // 65C02 BBR/BBS zp,rel
+    AbsoluteLong,            // 65816 long
+    AbsoluteLongX,           // 65816 long,X
+    AbsoluteIndirectLong,    // 65816 JML [abs]
+    ZeroPageIndirectLong,    // 65816 [dp]
+    ZeroPageIndirectLongY,   // 65816 [dp],Y
+    StackRelative,           // 65816 sr,S
+    StackRelativeIndirectY,  // 65816 (sr,S),Y
+    BlockMove,               // 65816 MVN/MVP srcbank,destbank
 }
 
 use strum_macros::EnumString;
@@ -71,6 +84,115 @@ pub enum OpCode {
     STX,
     LDY,
     LDX,
+    CPX,
+    CPY,
+    BIT,
+    JMP,
+    JSR,
+    RTI,
+    NOP,
+    // Undocumented NMOS instructions
+    #[strum(serialize = "KIL", serialize = "JAM", serialize = "HLT")]
+    KIL,
+    #[strum(serialize = "SLO", serialize = "ASO")]
+    SLO,
+    RLA,
+    #[strum(serialize = "SRE", serialize = "LSE")]
+    SRE,
+    RRA,
+    #[strum(serialize = "DCP", serialize = "DCM")]
+    DCP,
+    #[strum(serialize = "ISC", serialize = "ISB", serialize = "INS")]
+    ISC,
+    SAX,
+    LAX,
+    ANC,
+    #[strum(serialize = "ALR", serialize = "ASR")]
+    ALR,
+    ARR,
+    #[strum(serialize = "AXS", serialize = "SBX")]
+    AXS,
+    #[strum(serialize = "XAA", serialize = "ANE")]
+    XAA,
+    #[strum(serialize = "AHX", serialize = "SHA", serialize = "AXA")]
+    AHX,
+    #[strum(serialize = "SHY", serialize = "SYA", serialize = "SAY")]
+    SHY,
+    #[strum(serialize = "SHX", serialize = "SXA")]
+    SHX,
+    #[strum(serialize = "TAS", serialize = "SHS")]
+    TAS,
+    #[strum(serialize = "LAS", serialize = "LAR")]
+    LAS,
+    // 65C02 instructions
+    STZ,
+    TRB,
+    TSB,
+    PHX,
+    PHY,
+    PLX,
+    PLY,
+    RMB0,
+    RMB1,
+    RMB2,
+    RMB3,
+    RMB4,
+    RMB5,
+    RMB6,
+    RMB7,
+    SMB0,
+    SMB1,
+    SMB2,
+    SMB3,
+    SMB4,
+    SMB5,
+    SMB6,
+    SMB7,
+    BBR0,
+    BBR1,
+    BBR2,
+    BBR3,
+    BBR4,
+    BBR5,
+    BBR6,
+    BBR7,
+    BBS0,
+    BBS1,
+    BBS2,
+    BBS3,
+    BBS4,
+    BBS5,
+    BBS6,
+    BBS7,
+    WAI,
+    STP,
+    // 65816 instructions
+    BRL,
+    COP,
+    JML,
+    JSL,
+    RTL,
+    MVN,
+    MVP,
+    PEA,
+    PEI,
+    PER,
+    PHB,
+    PHD,
+    PHK,
+    PLB,
+    PLD,
+    REP,
+    SEP,
+    TCD,
+    TCS,
+    TDC,
+    TSC,
+    TXY,
+    TYX,
+    WDM,
+    XBA,
+    XCE,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy, Hash, Eq, PartialOrd, Ord)]
@@ -435,6 +557,65 @@ pub const INSTRUCTION_LOOKUP: phf::Map<u8, Instruction> = phf_map! {
         bytes: 2,
         cycles: 5, // +1 if page crossed
     },
+    // CPX Instructions
+    0xE0u8 => Instruction {
+        opname: OpCode::CPX,
+        opcode: 0xE0,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0xE4u8 => Instruction {
+        opname: OpCode::CPX,
+        opcode: 0xE4,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 3,
+    },
+    0xECu8 => Instruction {
+        opname: OpCode::CPX,
+        opcode: 0xEC,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 4,
+    },
+    // CPY Instructions
+    0xC0u8 => Instruction {
+        opname: OpCode::CPY,
+        opcode: 0xC0,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0xC4u8 => Instruction {
+        opname: OpCode::CPY,
+        opcode: 0xC4,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 3,
+    },
+    0xCCu8 => Instruction {
+        opname: OpCode::CPY,
+        opcode: 0xCC,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 4,
+    },
+    // BIT Instructions
+    0x24u8 => Instruction {
+        opname: OpCode::BIT,
+        opcode: 0x24,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 3,
+    },
+    0x2Cu8 => Instruction {
+        opname: OpCode::BIT,
+        opcode: 0x2C,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 4,
+    },
     // Branch Instructions
     0xF0u8 => Instruction {
         opname: OpCode::BEQ,
@@ -506,6 +687,42 @@ pub const INSTRUCTION_LOOKUP: phf::Map<u8, Instruction> = phf_map! {
         bytes: 1,
         cycles: 7,
     },
+    // Jump & Call Instructions
+    0x4Cu8 => Instruction {
+        opname: OpCode::JMP,
+        opcode: 0x4C,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 3,
+    },
+    0x6Cu8 => Instruction {
+        opname: OpCode::JMP,
+        opcode: 0x6C,
+        mode: AddressingMode::Indirect,
+        bytes: 3,
+        cycles: 5,
+    },
+    0x20u8 => Instruction {
+        opname: OpCode::JSR,
+        opcode: 0x20,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 6,
+    },
+    0x40u8 => Instruction {
+        opname: OpCode::RTI,
+        opcode: 0x40,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 6,
+    },
+    0xEAu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xEA,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
     // Register Transfers
     0xAAu8 => Instruction {
         opname: OpCode::TAX,
@@ -1089,80 +1306,2555 @@ pub const INSTRUCTION_LOOKUP: phf::Map<u8, Instruction> = phf_map! {
     },
 };
 
-pub fn create_opcode_map() -> HashMap<OpCode, Vec<Instruction>> {
-    let mut map = HashMap::new();
-
-    for instruction in INSTRUCTION_LOOKUP.values() {
-        map.entry(instruction.opname)
-            .or_insert_with(Vec::new)
-            .push(*instruction);
-    }
-
-    map
-}
-
-fn parse_operand(operand: &str) -> (AddressingMode, u16) {
-    if let Some(stripped) = operand.strip_prefix('#') {
-        // Immediate addressing - handle both #$2A and #42 formats
-        let value_str = stripped.trim_start_matches('$');
-        let value = u16::from_str_radix(value_str, 16).unwrap_or_else(|_| {
-            // Try parsing as decimal if hex fails
-            value_str.parse::<u16>().unwrap_or(0)
-        });
-        (AddressingMode::Immediate, value)
-    } else if let Some(stripped) = operand.strip_prefix('$') {
-        // Absolute or ZeroPage addressing
-        let value = u16::from_str_radix(stripped, 16).unwrap_or(0);
-        if value <= 0xFF {
-            (AddressingMode::ZeroPage, value)
-        } else {
-            (AddressingMode::Absolute, value)
-        }
-    } else {
-        // Try parsing as hex without prefix, then as decimal
-        let value = u16::from_str_radix(operand, 16)
-            .unwrap_or_else(|_| operand.parse::<u16>().unwrap_or(0));
-        (AddressingMode::Absolute, value)
-    }
-}
-
-pub fn assemble(source: &str) -> Vec<u8> {
-    let opcodes = create_opcode_map();
-    let mut machine_code = Vec::new();
-
-    for line in source.lines() {
-        let line = line.split(';').next().unwrap_or("").trim();
-        if line.is_empty() {
-            continue;
-        }
-
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        let mnemonic = OpCode::from_str(&parts[0].to_uppercase()).unwrap();
-
-        if let Some(instructions) = opcodes.get(&mnemonic) {
-            if parts.len() > 1 {
-                let (mode, value) = parse_operand(parts[1]);
-
-                // Find matching instruction for addressing mode
-                if let Some(instruction) = instructions.iter().find(|i| i.mode == mode) {
-                    machine_code.push(instruction.opcode);
-
-                    // Add operand bytes
-                    match instruction.bytes {
-                        2 => machine_code.push(value as u8),
-                        3 => {
-                            machine_code.push((value & 0xFF) as u8);
-                            machine_code.push((value >> 8) as u8);
-                        }
-                        _ => {}
-                    }
-                }
-            } else if let Some(instruction) = instructions
-                .iter()
-                .find(|i| i.mode == AddressingMode::Implied)
-            {
-                machine_code.push(instruction.opcode);
+/// Undocumented NMOS opcodes, decoded only by variants that enable them.
+pub const UNDOCUMENTED_LOOKUP: phf::Map<u8, Instruction> = phf_map! {
+    // KIL (JAM) - halts the processor
+    0x02u8 => Instruction {
+        opname: OpCode::KIL,
+        opcode: 0x02,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x12u8 => Instruction {
+        opname: OpCode::KIL,
+        opcode: 0x12,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x22u8 => Instruction {
+        opname: OpCode::KIL,
+        opcode: 0x22,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x32u8 => Instruction {
+        opname: OpCode::KIL,
+        opcode: 0x32,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x42u8 => Instruction {
+        opname: OpCode::KIL,
+        opcode: 0x42,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x52u8 => Instruction {
+        opname: OpCode::KIL,
+        opcode: 0x52,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x62u8 => Instruction {
+        opname: OpCode::KIL,
+        opcode: 0x62,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x72u8 => Instruction {
+        opname: OpCode::KIL,
+        opcode: 0x72,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x92u8 => Instruction {
+        opname: OpCode::KIL,
+        opcode: 0x92,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0xB2u8 => Instruction {
+        opname: OpCode::KIL,
+        opcode: 0xB2,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0xD2u8 => Instruction {
+        opname: OpCode::KIL,
+        opcode: 0xD2,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0xF2u8 => Instruction {
+        opname: OpCode::KIL,
+        opcode: 0xF2,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    // Undocumented NOPs
+    0x1Au8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x1A,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x3Au8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x3A,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x5Au8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x5A,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x7Au8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x7A,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0xDAu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xDA,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0xFAu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xFA,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x80u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x80,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0x82u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x82,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0x89u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x89,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0xC2u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xC2,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0xE2u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xE2,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0x04u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x04,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 3,
+    },
+    0x44u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x44,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 3,
+    },
+    0x64u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x64,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 3,
+    },
+    0x14u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x14,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x34u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x34,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x54u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x54,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x74u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x74,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 4,
+    },
+    0xD4u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xD4,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 4,
+    },
+    0xF4u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xF4,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x0Cu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x0C,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 4,
+    },
+    0x1Cu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x1C,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 4, // +1 if page crossed
+    },
+    0x3Cu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x3C,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 4, // +1 if page crossed
+    },
+    0x5Cu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x5C,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 4, // +1 if page crossed
+    },
+    0x7Cu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x7C,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 4, // +1 if page crossed
+    },
+    0xDCu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xDC,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 4, // +1 if page crossed
+    },
+    0xFCu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xFC,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 4, // +1 if page crossed
+    },
+    // SLO Instructions
+    0x07u8 => Instruction {
+        opname: OpCode::SLO,
+        opcode: 0x07,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x17u8 => Instruction {
+        opname: OpCode::SLO,
+        opcode: 0x17,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x0Fu8 => Instruction {
+        opname: OpCode::SLO,
+        opcode: 0x0F,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 6,
+    },
+    0x1Fu8 => Instruction {
+        opname: OpCode::SLO,
+        opcode: 0x1F,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 7,
+    },
+    0x1Bu8 => Instruction {
+        opname: OpCode::SLO,
+        opcode: 0x1B,
+        mode: AddressingMode::AbsoluteY,
+        bytes: 3,
+        cycles: 7,
+    },
+    0x03u8 => Instruction {
+        opname: OpCode::SLO,
+        opcode: 0x03,
+        mode: AddressingMode::IndirectX,
+        bytes: 2,
+        cycles: 8,
+    },
+    0x13u8 => Instruction {
+        opname: OpCode::SLO,
+        opcode: 0x13,
+        mode: AddressingMode::IndirectY,
+        bytes: 2,
+        cycles: 8,
+    },
+    // RLA Instructions
+    0x27u8 => Instruction {
+        opname: OpCode::RLA,
+        opcode: 0x27,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x37u8 => Instruction {
+        opname: OpCode::RLA,
+        opcode: 0x37,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x2Fu8 => Instruction {
+        opname: OpCode::RLA,
+        opcode: 0x2F,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 6,
+    },
+    0x3Fu8 => Instruction {
+        opname: OpCode::RLA,
+        opcode: 0x3F,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 7,
+    },
+    0x3Bu8 => Instruction {
+        opname: OpCode::RLA,
+        opcode: 0x3B,
+        mode: AddressingMode::AbsoluteY,
+        bytes: 3,
+        cycles: 7,
+    },
+    0x23u8 => Instruction {
+        opname: OpCode::RLA,
+        opcode: 0x23,
+        mode: AddressingMode::IndirectX,
+        bytes: 2,
+        cycles: 8,
+    },
+    0x33u8 => Instruction {
+        opname: OpCode::RLA,
+        opcode: 0x33,
+        mode: AddressingMode::IndirectY,
+        bytes: 2,
+        cycles: 8,
+    },
+    // SRE Instructions
+    0x47u8 => Instruction {
+        opname: OpCode::SRE,
+        opcode: 0x47,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x57u8 => Instruction {
+        opname: OpCode::SRE,
+        opcode: 0x57,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x4Fu8 => Instruction {
+        opname: OpCode::SRE,
+        opcode: 0x4F,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 6,
+    },
+    0x5Fu8 => Instruction {
+        opname: OpCode::SRE,
+        opcode: 0x5F,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 7,
+    },
+    0x5Bu8 => Instruction {
+        opname: OpCode::SRE,
+        opcode: 0x5B,
+        mode: AddressingMode::AbsoluteY,
+        bytes: 3,
+        cycles: 7,
+    },
+    0x43u8 => Instruction {
+        opname: OpCode::SRE,
+        opcode: 0x43,
+        mode: AddressingMode::IndirectX,
+        bytes: 2,
+        cycles: 8,
+    },
+    0x53u8 => Instruction {
+        opname: OpCode::SRE,
+        opcode: 0x53,
+        mode: AddressingMode::IndirectY,
+        bytes: 2,
+        cycles: 8,
+    },
+    // RRA Instructions
+    0x67u8 => Instruction {
+        opname: OpCode::RRA,
+        opcode: 0x67,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x77u8 => Instruction {
+        opname: OpCode::RRA,
+        opcode: 0x77,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x6Fu8 => Instruction {
+        opname: OpCode::RRA,
+        opcode: 0x6F,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 6,
+    },
+    0x7Fu8 => Instruction {
+        opname: OpCode::RRA,
+        opcode: 0x7F,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 7,
+    },
+    0x7Bu8 => Instruction {
+        opname: OpCode::RRA,
+        opcode: 0x7B,
+        mode: AddressingMode::AbsoluteY,
+        bytes: 3,
+        cycles: 7,
+    },
+    0x63u8 => Instruction {
+        opname: OpCode::RRA,
+        opcode: 0x63,
+        mode: AddressingMode::IndirectX,
+        bytes: 2,
+        cycles: 8,
+    },
+    0x73u8 => Instruction {
+        opname: OpCode::RRA,
+        opcode: 0x73,
+        mode: AddressingMode::IndirectY,
+        bytes: 2,
+        cycles: 8,
+    },
+    // DCP Instructions
+    0xC7u8 => Instruction {
+        opname: OpCode::DCP,
+        opcode: 0xC7,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xD7u8 => Instruction {
+        opname: OpCode::DCP,
+        opcode: 0xD7,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 6,
+    },
+    0xCFu8 => Instruction {
+        opname: OpCode::DCP,
+        opcode: 0xCF,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 6,
+    },
+    0xDFu8 => Instruction {
+        opname: OpCode::DCP,
+        opcode: 0xDF,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 7,
+    },
+    0xDBu8 => Instruction {
+        opname: OpCode::DCP,
+        opcode: 0xDB,
+        mode: AddressingMode::AbsoluteY,
+        bytes: 3,
+        cycles: 7,
+    },
+    0xC3u8 => Instruction {
+        opname: OpCode::DCP,
+        opcode: 0xC3,
+        mode: AddressingMode::IndirectX,
+        bytes: 2,
+        cycles: 8,
+    },
+    0xD3u8 => Instruction {
+        opname: OpCode::DCP,
+        opcode: 0xD3,
+        mode: AddressingMode::IndirectY,
+        bytes: 2,
+        cycles: 8,
+    },
+    // ISC Instructions
+    0xE7u8 => Instruction {
+        opname: OpCode::ISC,
+        opcode: 0xE7,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xF7u8 => Instruction {
+        opname: OpCode::ISC,
+        opcode: 0xF7,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 6,
+    },
+    0xEFu8 => Instruction {
+        opname: OpCode::ISC,
+        opcode: 0xEF,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 6,
+    },
+    0xFFu8 => Instruction {
+        opname: OpCode::ISC,
+        opcode: 0xFF,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 7,
+    },
+    0xFBu8 => Instruction {
+        opname: OpCode::ISC,
+        opcode: 0xFB,
+        mode: AddressingMode::AbsoluteY,
+        bytes: 3,
+        cycles: 7,
+    },
+    0xE3u8 => Instruction {
+        opname: OpCode::ISC,
+        opcode: 0xE3,
+        mode: AddressingMode::IndirectX,
+        bytes: 2,
+        cycles: 8,
+    },
+    0xF3u8 => Instruction {
+        opname: OpCode::ISC,
+        opcode: 0xF3,
+        mode: AddressingMode::IndirectY,
+        bytes: 2,
+        cycles: 8,
+    },
+    // SAX Instructions
+    0x87u8 => Instruction {
+        opname: OpCode::SAX,
+        opcode: 0x87,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 3,
+    },
+    0x97u8 => Instruction {
+        opname: OpCode::SAX,
+        opcode: 0x97,
+        mode: AddressingMode::ZeroPageY,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x8Fu8 => Instruction {
+        opname: OpCode::SAX,
+        opcode: 0x8F,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 4,
+    },
+    0x83u8 => Instruction {
+        opname: OpCode::SAX,
+        opcode: 0x83,
+        mode: AddressingMode::IndirectX,
+        bytes: 2,
+        cycles: 6,
+    },
+    // LAX Instructions
+    0xA7u8 => Instruction {
+        opname: OpCode::LAX,
+        opcode: 0xA7,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 3,
+    },
+    0xB7u8 => Instruction {
+        opname: OpCode::LAX,
+        opcode: 0xB7,
+        mode: AddressingMode::ZeroPageY,
+        bytes: 2,
+        cycles: 4,
+    },
+    0xAFu8 => Instruction {
+        opname: OpCode::LAX,
+        opcode: 0xAF,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 4,
+    },
+    0xBFu8 => Instruction {
+        opname: OpCode::LAX,
+        opcode: 0xBF,
+        mode: AddressingMode::AbsoluteY,
+        bytes: 3,
+        cycles: 4, // +1 if page crossed
+    },
+    0xA3u8 => Instruction {
+        opname: OpCode::LAX,
+        opcode: 0xA3,
+        mode: AddressingMode::IndirectX,
+        bytes: 2,
+        cycles: 6,
+    },
+    0xB3u8 => Instruction {
+        opname: OpCode::LAX,
+        opcode: 0xB3,
+        mode: AddressingMode::IndirectY,
+        bytes: 2,
+        cycles: 5, // +1 if page crossed
+    },
+    0xABu8 => Instruction {
+        opname: OpCode::LAX,
+        opcode: 0xAB,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2, // Unstable, modeled with a magic constant of $EE
+    },
+    // Immediate-only combinations
+    0x0Bu8 => Instruction {
+        opname: OpCode::ANC,
+        opcode: 0x0B,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0x2Bu8 => Instruction {
+        opname: OpCode::ANC,
+        opcode: 0x2B,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0x4Bu8 => Instruction {
+        opname: OpCode::ALR,
+        opcode: 0x4B,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0x6Bu8 => Instruction {
+        opname: OpCode::ARR,
+        opcode: 0x6B,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0xCBu8 => Instruction {
+        opname: OpCode::AXS,
+        opcode: 0xCB,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0xEBu8 => Instruction {
+        opname: OpCode::SBC,
+        opcode: 0xEB,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0x8Bu8 => Instruction {
+        opname: OpCode::XAA,
+        opcode: 0x8B,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2, // Unstable, modeled with a magic constant of $EE
+    },
+    // Stores ANDed with the high byte of the address plus one
+    0x93u8 => Instruction {
+        opname: OpCode::AHX,
+        opcode: 0x93,
+        mode: AddressingMode::IndirectY,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x9Fu8 => Instruction {
+        opname: OpCode::AHX,
+        opcode: 0x9F,
+        mode: AddressingMode::AbsoluteY,
+        bytes: 3,
+        cycles: 5,
+    },
+    0x9Cu8 => Instruction {
+        opname: OpCode::SHY,
+        opcode: 0x9C,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 5,
+    },
+    0x9Eu8 => Instruction {
+        opname: OpCode::SHX,
+        opcode: 0x9E,
+        mode: AddressingMode::AbsoluteY,
+        bytes: 3,
+        cycles: 5,
+    },
+    0x9Bu8 => Instruction {
+        opname: OpCode::TAS,
+        opcode: 0x9B,
+        mode: AddressingMode::AbsoluteY,
+        bytes: 3,
+        cycles: 5,
+    },
+    0xBBu8 => Instruction {
+        opname: OpCode::LAS,
+        opcode: 0xBB,
+        mode: AddressingMode::AbsoluteY,
+        bytes: 3,
+        cycles: 4, // +1 if page crossed
+    },
+};
+
+/// WDC 65C02 additions, and the NMOS opcodes it times differently. Decoded
+/// in place of the NMOS table by CMOS variants.
+pub const CMOS_LOOKUP: phf::Map<u8, Instruction> = phf_map! {
+    // Zero Page Indirect Instructions
+    0x12u8 => Instruction {
+        opname: OpCode::ORA,
+        opcode: 0x12,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x32u8 => Instruction {
+        opname: OpCode::AND,
+        opcode: 0x32,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x52u8 => Instruction {
+        opname: OpCode::EOR,
+        opcode: 0x52,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x72u8 => Instruction {
+        opname: OpCode::ADC,
+        opcode: 0x72,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5, // +1 in decimal mode
+    },
+    0x92u8 => Instruction {
+        opname: OpCode::STA,
+        opcode: 0x92,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xB2u8 => Instruction {
+        opname: OpCode::LDA,
+        opcode: 0xB2,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xD2u8 => Instruction {
+        opname: OpCode::CMP,
+        opcode: 0xD2,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xF2u8 => Instruction {
+        opname: OpCode::SBC,
+        opcode: 0xF2,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5, // +1 in decimal mode
+    },
+    // BIT Instructions
+    0x89u8 => Instruction {
+        opname: OpCode::BIT,
+        opcode: 0x89,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2, // Only affects Z
+    },
+    0x34u8 => Instruction {
+        opname: OpCode::BIT,
+        opcode: 0x34,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x3Cu8 => Instruction {
+        opname: OpCode::BIT,
+        opcode: 0x3C,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 4, // +1 if page crossed
+    },
+    // INC and DEC Accumulator
+    0x1Au8 => Instruction {
+        opname: OpCode::INC,
+        opcode: 0x1A,
+        mode: AddressingMode::Implied, // Accumulator
+        bytes: 1,
+        cycles: 2,
+    },
+    0x3Au8 => Instruction {
+        opname: OpCode::DEC,
+        opcode: 0x3A,
+        mode: AddressingMode::Implied, // Accumulator
+        bytes: 1,
+        cycles: 2,
+    },
+    // STZ Instructions
+    0x64u8 => Instruction {
+        opname: OpCode::STZ,
+        opcode: 0x64,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 3,
+    },
+    0x74u8 => Instruction {
+        opname: OpCode::STZ,
+        opcode: 0x74,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x9Cu8 => Instruction {
+        opname: OpCode::STZ,
+        opcode: 0x9C,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 4,
+    },
+    0x9Eu8 => Instruction {
+        opname: OpCode::STZ,
+        opcode: 0x9E,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 5,
+    },
+    // TSB and TRB Instructions
+    0x04u8 => Instruction {
+        opname: OpCode::TSB,
+        opcode: 0x04,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x0Cu8 => Instruction {
+        opname: OpCode::TSB,
+        opcode: 0x0C,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 6,
+    },
+    0x14u8 => Instruction {
+        opname: OpCode::TRB,
+        opcode: 0x14,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x1Cu8 => Instruction {
+        opname: OpCode::TRB,
+        opcode: 0x1C,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 6,
+    },
+    // Stack Operations
+    0x5Au8 => Instruction {
+        opname: OpCode::PHY,
+        opcode: 0x5A,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 3,
+    },
+    0x7Au8 => Instruction {
+        opname: OpCode::PLY,
+        opcode: 0x7A,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 4,
+    },
+    0xDAu8 => Instruction {
+        opname: OpCode::PHX,
+        opcode: 0xDA,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 3,
+    },
+    0xFAu8 => Instruction {
+        opname: OpCode::PLX,
+        opcode: 0xFA,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 4,
+    },
+    // Branch Always
+    0x80u8 => Instruction {
+        opname: OpCode::BRA,
+        opcode: 0x80,
+        mode: AddressingMode::Immediate, // Note: 6502 uses Relative
+        bytes: 2,
+        cycles: 2, // +1 always taken, +1 more if page crossed
+    },
+    // Jump Instructions
+    0x6Cu8 => Instruction {
+        opname: OpCode::JMP,
+        opcode: 0x6C,
+        mode: AddressingMode::Indirect,
+        bytes: 3,
+        cycles: 6, // Page wrap bug fixed at the cost of a cycle
+    },
+    0x7Cu8 => Instruction {
+        opname: OpCode::JMP,
+        opcode: 0x7C,
+        mode: AddressingMode::AbsoluteIndexedIndirect,
+        bytes: 3,
+        cycles: 6,
+    },
+    // Shifts that only spend the fix-up cycle on a page cross
+    0x1Eu8 => Instruction {
+        opname: OpCode::ASL,
+        opcode: 0x1E,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 6, // +1 if page crossed
+    },
+    0x3Eu8 => Instruction {
+        opname: OpCode::ROL,
+        opcode: 0x3E,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 6, // +1 if page crossed
+    },
+    0x5Eu8 => Instruction {
+        opname: OpCode::LSR,
+        opcode: 0x5E,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 6, // +1 if page crossed
+    },
+    0x7Eu8 => Instruction {
+        opname: OpCode::ROR,
+        opcode: 0x7E,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 6, // +1 if page crossed
+    },
+    // RMB and SMB Instructions
+    0x07u8 => Instruction {
+        opname: OpCode::RMB0,
+        opcode: 0x07,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x17u8 => Instruction {
+        opname: OpCode::RMB1,
+        opcode: 0x17,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x27u8 => Instruction {
+        opname: OpCode::RMB2,
+        opcode: 0x27,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x37u8 => Instruction {
+        opname: OpCode::RMB3,
+        opcode: 0x37,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x47u8 => Instruction {
+        opname: OpCode::RMB4,
+        opcode: 0x47,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x57u8 => Instruction {
+        opname: OpCode::RMB5,
+        opcode: 0x57,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x67u8 => Instruction {
+        opname: OpCode::RMB6,
+        opcode: 0x67,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x77u8 => Instruction {
+        opname: OpCode::RMB7,
+        opcode: 0x77,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x87u8 => Instruction {
+        opname: OpCode::SMB0,
+        opcode: 0x87,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x97u8 => Instruction {
+        opname: OpCode::SMB1,
+        opcode: 0x97,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xA7u8 => Instruction {
+        opname: OpCode::SMB2,
+        opcode: 0xA7,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xB7u8 => Instruction {
+        opname: OpCode::SMB3,
+        opcode: 0xB7,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xC7u8 => Instruction {
+        opname: OpCode::SMB4,
+        opcode: 0xC7,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xD7u8 => Instruction {
+        opname: OpCode::SMB5,
+        opcode: 0xD7,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xE7u8 => Instruction {
+        opname: OpCode::SMB6,
+        opcode: 0xE7,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xF7u8 => Instruction {
+        opname: OpCode::SMB7,
+        opcode: 0xF7,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    // BBR and BBS Instructions
+    0x0Fu8 => Instruction {
+        opname: OpCode::BBR0,
+        opcode: 0x0F,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0x1Fu8 => Instruction {
+        opname: OpCode::BBR1,
+        opcode: 0x1F,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0x2Fu8 => Instruction {
+        opname: OpCode::BBR2,
+        opcode: 0x2F,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0x3Fu8 => Instruction {
+        opname: OpCode::BBR3,
+        opcode: 0x3F,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0x4Fu8 => Instruction {
+        opname: OpCode::BBR4,
+        opcode: 0x4F,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0x5Fu8 => Instruction {
+        opname: OpCode::BBR5,
+        opcode: 0x5F,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0x6Fu8 => Instruction {
+        opname: OpCode::BBR6,
+        opcode: 0x6F,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0x7Fu8 => Instruction {
+        opname: OpCode::BBR7,
+        opcode: 0x7F,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0x8Fu8 => Instruction {
+        opname: OpCode::BBS0,
+        opcode: 0x8F,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0x9Fu8 => Instruction {
+        opname: OpCode::BBS1,
+        opcode: 0x9F,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0xAFu8 => Instruction {
+        opname: OpCode::BBS2,
+        opcode: 0xAF,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0xBFu8 => Instruction {
+        opname: OpCode::BBS3,
+        opcode: 0xBF,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0xCFu8 => Instruction {
+        opname: OpCode::BBS4,
+        opcode: 0xCF,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0xDFu8 => Instruction {
+        opname: OpCode::BBS5,
+        opcode: 0xDF,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0xEFu8 => Instruction {
+        opname: OpCode::BBS6,
+        opcode: 0xEF,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    0xFFu8 => Instruction {
+        opname: OpCode::BBS7,
+        opcode: 0xFF,
+        mode: AddressingMode::ZeroPageRelative,
+        bytes: 3,
+        cycles: 5, // +1 if branch succeeds, +2 if page crossed
+    },
+    // Wait for Interrupt and Stop
+    0xCBu8 => Instruction {
+        opname: OpCode::WAI,
+        opcode: 0xCB,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 3,
+    },
+    0xDBu8 => Instruction {
+        opname: OpCode::STP,
+        opcode: 0xDB,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 3,
+    },
+    // Unused opcodes are NOPs of fixed length on the 65C02
+    0x02u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x02,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0x22u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x22,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0x42u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x42,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0x62u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x62,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0x82u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x82,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0xC2u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xC2,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0xE2u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xE2,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0x44u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x44,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 3,
+    },
+    0x54u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x54,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 4,
+    },
+    0xD4u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xD4,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 4,
+    },
+    0xF4u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xF4,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x5Cu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x5C,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 8,
+    },
+    0xDCu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xDC,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 4,
+    },
+    0xFCu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xFC,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 4,
+    },
+    0x03u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x03,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x0Bu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x0B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x13u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x13,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x1Bu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x1B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x23u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x23,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x2Bu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x2B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x33u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x33,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x3Bu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x3B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x43u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x43,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x4Bu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x4B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x53u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x53,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x5Bu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x5B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x63u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x63,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x6Bu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x6B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x73u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x73,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x7Bu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x7B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x83u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x83,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x8Bu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x8B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x93u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x93,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0x9Bu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0x9B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0xA3u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xA3,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0xABu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xAB,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0xB3u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xB3,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0xBBu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xBB,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0xC3u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xC3,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0xD3u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xD3,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0xE3u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xE3,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0xEBu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xEB,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0xF3u8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xF3,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+    0xFBu8 => Instruction {
+        opname: OpCode::NOP,
+        opcode: 0xFB,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 1,
+    },
+};
+
+/// W65C816 additions and changes to the NMOS set. Immediate operands of
+/// accumulator and index instructions gain a byte while M or X is clear.
+pub const W65C816_LOOKUP: phf::Map<u8, Instruction> = phf_map! {
+    // Stack relative, long and zero page indirect forms of the accumulator instructions
+    0x03u8 => Instruction {
+        opname: OpCode::ORA,
+        opcode: 0x03,
+        mode: AddressingMode::StackRelative,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x07u8 => Instruction {
+        opname: OpCode::ORA,
+        opcode: 0x07,
+        mode: AddressingMode::ZeroPageIndirectLong,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x0Fu8 => Instruction {
+        opname: OpCode::ORA,
+        opcode: 0x0F,
+        mode: AddressingMode::AbsoluteLong,
+        bytes: 4,
+        cycles: 5,
+    },
+    0x12u8 => Instruction {
+        opname: OpCode::ORA,
+        opcode: 0x12,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x13u8 => Instruction {
+        opname: OpCode::ORA,
+        opcode: 0x13,
+        mode: AddressingMode::StackRelativeIndirectY,
+        bytes: 2,
+        cycles: 7,
+    },
+    0x17u8 => Instruction {
+        opname: OpCode::ORA,
+        opcode: 0x17,
+        mode: AddressingMode::ZeroPageIndirectLongY,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x1Fu8 => Instruction {
+        opname: OpCode::ORA,
+        opcode: 0x1F,
+        mode: AddressingMode::AbsoluteLongX,
+        bytes: 4,
+        cycles: 5,
+    },
+    0x23u8 => Instruction {
+        opname: OpCode::AND,
+        opcode: 0x23,
+        mode: AddressingMode::StackRelative,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x27u8 => Instruction {
+        opname: OpCode::AND,
+        opcode: 0x27,
+        mode: AddressingMode::ZeroPageIndirectLong,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x2Fu8 => Instruction {
+        opname: OpCode::AND,
+        opcode: 0x2F,
+        mode: AddressingMode::AbsoluteLong,
+        bytes: 4,
+        cycles: 5,
+    },
+    0x32u8 => Instruction {
+        opname: OpCode::AND,
+        opcode: 0x32,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x33u8 => Instruction {
+        opname: OpCode::AND,
+        opcode: 0x33,
+        mode: AddressingMode::StackRelativeIndirectY,
+        bytes: 2,
+        cycles: 7,
+    },
+    0x37u8 => Instruction {
+        opname: OpCode::AND,
+        opcode: 0x37,
+        mode: AddressingMode::ZeroPageIndirectLongY,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x3Fu8 => Instruction {
+        opname: OpCode::AND,
+        opcode: 0x3F,
+        mode: AddressingMode::AbsoluteLongX,
+        bytes: 4,
+        cycles: 5,
+    },
+    0x43u8 => Instruction {
+        opname: OpCode::EOR,
+        opcode: 0x43,
+        mode: AddressingMode::StackRelative,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x47u8 => Instruction {
+        opname: OpCode::EOR,
+        opcode: 0x47,
+        mode: AddressingMode::ZeroPageIndirectLong,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x4Fu8 => Instruction {
+        opname: OpCode::EOR,
+        opcode: 0x4F,
+        mode: AddressingMode::AbsoluteLong,
+        bytes: 4,
+        cycles: 5,
+    },
+    0x52u8 => Instruction {
+        opname: OpCode::EOR,
+        opcode: 0x52,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x53u8 => Instruction {
+        opname: OpCode::EOR,
+        opcode: 0x53,
+        mode: AddressingMode::StackRelativeIndirectY,
+        bytes: 2,
+        cycles: 7,
+    },
+    0x57u8 => Instruction {
+        opname: OpCode::EOR,
+        opcode: 0x57,
+        mode: AddressingMode::ZeroPageIndirectLongY,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x5Fu8 => Instruction {
+        opname: OpCode::EOR,
+        opcode: 0x5F,
+        mode: AddressingMode::AbsoluteLongX,
+        bytes: 4,
+        cycles: 5,
+    },
+    0x63u8 => Instruction {
+        opname: OpCode::ADC,
+        opcode: 0x63,
+        mode: AddressingMode::StackRelative,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x67u8 => Instruction {
+        opname: OpCode::ADC,
+        opcode: 0x67,
+        mode: AddressingMode::ZeroPageIndirectLong,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x6Fu8 => Instruction {
+        opname: OpCode::ADC,
+        opcode: 0x6F,
+        mode: AddressingMode::AbsoluteLong,
+        bytes: 4,
+        cycles: 5,
+    },
+    0x72u8 => Instruction {
+        opname: OpCode::ADC,
+        opcode: 0x72,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x73u8 => Instruction {
+        opname: OpCode::ADC,
+        opcode: 0x73,
+        mode: AddressingMode::StackRelativeIndirectY,
+        bytes: 2,
+        cycles: 7,
+    },
+    0x77u8 => Instruction {
+        opname: OpCode::ADC,
+        opcode: 0x77,
+        mode: AddressingMode::ZeroPageIndirectLongY,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x7Fu8 => Instruction {
+        opname: OpCode::ADC,
+        opcode: 0x7F,
+        mode: AddressingMode::AbsoluteLongX,
+        bytes: 4,
+        cycles: 5,
+    },
+    0x83u8 => Instruction {
+        opname: OpCode::STA,
+        opcode: 0x83,
+        mode: AddressingMode::StackRelative,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x87u8 => Instruction {
+        opname: OpCode::STA,
+        opcode: 0x87,
+        mode: AddressingMode::ZeroPageIndirectLong,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x8Fu8 => Instruction {
+        opname: OpCode::STA,
+        opcode: 0x8F,
+        mode: AddressingMode::AbsoluteLong,
+        bytes: 4,
+        cycles: 5,
+    },
+    0x92u8 => Instruction {
+        opname: OpCode::STA,
+        opcode: 0x92,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x93u8 => Instruction {
+        opname: OpCode::STA,
+        opcode: 0x93,
+        mode: AddressingMode::StackRelativeIndirectY,
+        bytes: 2,
+        cycles: 7,
+    },
+    0x97u8 => Instruction {
+        opname: OpCode::STA,
+        opcode: 0x97,
+        mode: AddressingMode::ZeroPageIndirectLongY,
+        bytes: 2,
+        cycles: 6,
+    },
+    0x9Fu8 => Instruction {
+        opname: OpCode::STA,
+        opcode: 0x9F,
+        mode: AddressingMode::AbsoluteLongX,
+        bytes: 4,
+        cycles: 5,
+    },
+    0xA3u8 => Instruction {
+        opname: OpCode::LDA,
+        opcode: 0xA3,
+        mode: AddressingMode::StackRelative,
+        bytes: 2,
+        cycles: 4,
+    },
+    0xA7u8 => Instruction {
+        opname: OpCode::LDA,
+        opcode: 0xA7,
+        mode: AddressingMode::ZeroPageIndirectLong,
+        bytes: 2,
+        cycles: 6,
+    },
+    0xAFu8 => Instruction {
+        opname: OpCode::LDA,
+        opcode: 0xAF,
+        mode: AddressingMode::AbsoluteLong,
+        bytes: 4,
+        cycles: 5,
+    },
+    0xB2u8 => Instruction {
+        opname: OpCode::LDA,
+        opcode: 0xB2,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xB3u8 => Instruction {
+        opname: OpCode::LDA,
+        opcode: 0xB3,
+        mode: AddressingMode::StackRelativeIndirectY,
+        bytes: 2,
+        cycles: 7,
+    },
+    0xB7u8 => Instruction {
+        opname: OpCode::LDA,
+        opcode: 0xB7,
+        mode: AddressingMode::ZeroPageIndirectLongY,
+        bytes: 2,
+        cycles: 6,
+    },
+    0xBFu8 => Instruction {
+        opname: OpCode::LDA,
+        opcode: 0xBF,
+        mode: AddressingMode::AbsoluteLongX,
+        bytes: 4,
+        cycles: 5,
+    },
+    0xC3u8 => Instruction {
+        opname: OpCode::CMP,
+        opcode: 0xC3,
+        mode: AddressingMode::StackRelative,
+        bytes: 2,
+        cycles: 4,
+    },
+    0xC7u8 => Instruction {
+        opname: OpCode::CMP,
+        opcode: 0xC7,
+        mode: AddressingMode::ZeroPageIndirectLong,
+        bytes: 2,
+        cycles: 6,
+    },
+    0xCFu8 => Instruction {
+        opname: OpCode::CMP,
+        opcode: 0xCF,
+        mode: AddressingMode::AbsoluteLong,
+        bytes: 4,
+        cycles: 5,
+    },
+    0xD2u8 => Instruction {
+        opname: OpCode::CMP,
+        opcode: 0xD2,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xD3u8 => Instruction {
+        opname: OpCode::CMP,
+        opcode: 0xD3,
+        mode: AddressingMode::StackRelativeIndirectY,
+        bytes: 2,
+        cycles: 7,
+    },
+    0xD7u8 => Instruction {
+        opname: OpCode::CMP,
+        opcode: 0xD7,
+        mode: AddressingMode::ZeroPageIndirectLongY,
+        bytes: 2,
+        cycles: 6,
+    },
+    0xDFu8 => Instruction {
+        opname: OpCode::CMP,
+        opcode: 0xDF,
+        mode: AddressingMode::AbsoluteLongX,
+        bytes: 4,
+        cycles: 5,
+    },
+    0xE3u8 => Instruction {
+        opname: OpCode::SBC,
+        opcode: 0xE3,
+        mode: AddressingMode::StackRelative,
+        bytes: 2,
+        cycles: 4,
+    },
+    0xE7u8 => Instruction {
+        opname: OpCode::SBC,
+        opcode: 0xE7,
+        mode: AddressingMode::ZeroPageIndirectLong,
+        bytes: 2,
+        cycles: 6,
+    },
+    0xEFu8 => Instruction {
+        opname: OpCode::SBC,
+        opcode: 0xEF,
+        mode: AddressingMode::AbsoluteLong,
+        bytes: 4,
+        cycles: 5,
+    },
+    0xF2u8 => Instruction {
+        opname: OpCode::SBC,
+        opcode: 0xF2,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 5,
+    },
+    0xF3u8 => Instruction {
+        opname: OpCode::SBC,
+        opcode: 0xF3,
+        mode: AddressingMode::StackRelativeIndirectY,
+        bytes: 2,
+        cycles: 7,
+    },
+    0xF7u8 => Instruction {
+        opname: OpCode::SBC,
+        opcode: 0xF7,
+        mode: AddressingMode::ZeroPageIndirectLongY,
+        bytes: 2,
+        cycles: 6,
+    },
+    0xFFu8 => Instruction {
+        opname: OpCode::SBC,
+        opcode: 0xFF,
+        mode: AddressingMode::AbsoluteLongX,
+        bytes: 4,
+        cycles: 5,
+    },
+    // Instructions shared with the 65C02
+    0x04u8 => Instruction {
+        opname: OpCode::TSB,
+        opcode: 0x04,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x0Cu8 => Instruction {
+        opname: OpCode::TSB,
+        opcode: 0x0C,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 6,
+    },
+    0x14u8 => Instruction {
+        opname: OpCode::TRB,
+        opcode: 0x14,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 5,
+    },
+    0x1Au8 => Instruction {
+        opname: OpCode::INC,
+        opcode: 0x1A,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x1Cu8 => Instruction {
+        opname: OpCode::TRB,
+        opcode: 0x1C,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 6,
+    },
+    0x34u8 => Instruction {
+        opname: OpCode::BIT,
+        opcode: 0x34,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x3Au8 => Instruction {
+        opname: OpCode::DEC,
+        opcode: 0x3A,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x3Cu8 => Instruction {
+        opname: OpCode::BIT,
+        opcode: 0x3C,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 4,
+    },
+    0x5Au8 => Instruction {
+        opname: OpCode::PHY,
+        opcode: 0x5A,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 3,
+    },
+    0x64u8 => Instruction {
+        opname: OpCode::STZ,
+        opcode: 0x64,
+        mode: AddressingMode::ZeroPage,
+        bytes: 2,
+        cycles: 3,
+    },
+    0x74u8 => Instruction {
+        opname: OpCode::STZ,
+        opcode: 0x74,
+        mode: AddressingMode::ZeroPageX,
+        bytes: 2,
+        cycles: 4,
+    },
+    0x7Au8 => Instruction {
+        opname: OpCode::PLY,
+        opcode: 0x7A,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 4,
+    },
+    0x7Cu8 => Instruction {
+        opname: OpCode::JMP,
+        opcode: 0x7C,
+        mode: AddressingMode::AbsoluteIndexedIndirect,
+        bytes: 3,
+        cycles: 6,
+    },
+    0x80u8 => Instruction {
+        opname: OpCode::BRA,
+        opcode: 0x80,
+        mode: AddressingMode::Immediate, // Note: 6502 uses Relative
+        bytes: 2,
+        cycles: 2, // +1 if branch succeeds, +2 if page crossed
+    },
+    0x89u8 => Instruction {
+        opname: OpCode::BIT,
+        opcode: 0x89,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+    0x9Cu8 => Instruction {
+        opname: OpCode::STZ,
+        opcode: 0x9C,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 4,
+    },
+    0x9Eu8 => Instruction {
+        opname: OpCode::STZ,
+        opcode: 0x9E,
+        mode: AddressingMode::AbsoluteX,
+        bytes: 3,
+        cycles: 5,
+    },
+    0xCBu8 => Instruction {
+        opname: OpCode::WAI,
+        opcode: 0xCB,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 3,
+    },
+    0xDAu8 => Instruction {
+        opname: OpCode::PHX,
+        opcode: 0xDA,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 3,
+    },
+    0xDBu8 => Instruction {
+        opname: OpCode::STP,
+        opcode: 0xDB,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 3,
+    },
+    0xFAu8 => Instruction {
+        opname: OpCode::PLX,
+        opcode: 0xFA,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 4,
+    },
+    // Interrupts
+    0x00u8 => Instruction {
+        opname: OpCode::BRK,
+        opcode: 0x00,
+        mode: AddressingMode::Immediate, // Signature byte
+        bytes: 2,
+        cycles: 7, // +1 in native mode
+    },
+    0x02u8 => Instruction {
+        opname: OpCode::COP,
+        opcode: 0x02,
+        mode: AddressingMode::Immediate, // Signature byte
+        bytes: 2,
+        cycles: 7, // +1 in native mode
+    },
+    0x40u8 => Instruction {
+        opname: OpCode::RTI,
+        opcode: 0x40,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 6, // +1 in native mode
+    },
+    // Jump & Call Instructions
+    0x22u8 => Instruction {
+        opname: OpCode::JSL,
+        opcode: 0x22,
+        mode: AddressingMode::AbsoluteLong,
+        bytes: 4,
+        cycles: 8,
+    },
+    0x5Cu8 => Instruction {
+        opname: OpCode::JML,
+        opcode: 0x5C,
+        mode: AddressingMode::AbsoluteLong,
+        bytes: 4,
+        cycles: 4,
+    },
+    0x6Bu8 => Instruction {
+        opname: OpCode::RTL,
+        opcode: 0x6B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 6,
+    },
+    0x82u8 => Instruction {
+        opname: OpCode::BRL,
+        opcode: 0x82,
+        mode: AddressingMode::Absolute, // Note: 65816 uses Relative Long
+        bytes: 3,
+        cycles: 4,
+    },
+    0xDCu8 => Instruction {
+        opname: OpCode::JML,
+        opcode: 0xDC,
+        mode: AddressingMode::AbsoluteIndirectLong,
+        bytes: 3,
+        cycles: 6,
+    },
+    0xFCu8 => Instruction {
+        opname: OpCode::JSR,
+        opcode: 0xFC,
+        mode: AddressingMode::AbsoluteIndexedIndirect,
+        bytes: 3,
+        cycles: 8,
+    },
+    // Block Moves
+    0x44u8 => Instruction {
+        opname: OpCode::MVP,
+        opcode: 0x44,
+        mode: AddressingMode::BlockMove,
+        bytes: 3,
+        cycles: 7, // Per byte moved
+    },
+    0x54u8 => Instruction {
+        opname: OpCode::MVN,
+        opcode: 0x54,
+        mode: AddressingMode::BlockMove,
+        bytes: 3,
+        cycles: 7, // Per byte moved
+    },
+    // Stack Operations
+    0x0Bu8 => Instruction {
+        opname: OpCode::PHD,
+        opcode: 0x0B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 4,
+    },
+    0x2Bu8 => Instruction {
+        opname: OpCode::PLD,
+        opcode: 0x2B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 5,
+    },
+    0x4Bu8 => Instruction {
+        opname: OpCode::PHK,
+        opcode: 0x4B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 3,
+    },
+    0x62u8 => Instruction {
+        opname: OpCode::PER,
+        opcode: 0x62,
+        mode: AddressingMode::Absolute, // Note: 65816 uses Relative Long
+        bytes: 3,
+        cycles: 6,
+    },
+    0x8Bu8 => Instruction {
+        opname: OpCode::PHB,
+        opcode: 0x8B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 3,
+    },
+    0xABu8 => Instruction {
+        opname: OpCode::PLB,
+        opcode: 0xAB,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 4,
+    },
+    0xD4u8 => Instruction {
+        opname: OpCode::PEI,
+        opcode: 0xD4,
+        mode: AddressingMode::ZeroPageIndirect,
+        bytes: 2,
+        cycles: 6,
+    },
+    0xF4u8 => Instruction {
+        opname: OpCode::PEA,
+        opcode: 0xF4,
+        mode: AddressingMode::Absolute,
+        bytes: 3,
+        cycles: 5,
+    },
+    // Register Transfers
+    0x1Bu8 => Instruction {
+        opname: OpCode::TCS,
+        opcode: 0x1B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x3Bu8 => Instruction {
+        opname: OpCode::TSC,
+        opcode: 0x3B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x5Bu8 => Instruction {
+        opname: OpCode::TCD,
+        opcode: 0x5B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x7Bu8 => Instruction {
+        opname: OpCode::TDC,
+        opcode: 0x7B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0x9Bu8 => Instruction {
+        opname: OpCode::TXY,
+        opcode: 0x9B,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0xBBu8 => Instruction {
+        opname: OpCode::TYX,
+        opcode: 0xBB,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    0xEBu8 => Instruction {
+        opname: OpCode::XBA,
+        opcode: 0xEB,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 3,
+    },
+    // Status and mode changes
+    0xC2u8 => Instruction {
+        opname: OpCode::REP,
+        opcode: 0xC2,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 3,
+    },
+    0xE2u8 => Instruction {
+        opname: OpCode::SEP,
+        opcode: 0xE2,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 3,
+    },
+    0xFBu8 => Instruction {
+        opname: OpCode::XCE,
+        opcode: 0xFB,
+        mode: AddressingMode::Implied,
+        bytes: 1,
+        cycles: 2,
+    },
+    // Reserved
+    0x42u8 => Instruction {
+        opname: OpCode::WDM,
+        opcode: 0x42,
+        mode: AddressingMode::Immediate,
+        bytes: 2,
+        cycles: 2,
+    },
+};
+
+pub fn create_opcode_map() -> HashMap<OpCode, Vec<Instruction>> {
+    create_opcode_map_for(CpuVariant::default())
+}
+
+// Documented encodings are listed first so they win when an undocumented
+// opcode duplicates one, such as $EB for SBC #imm.
+pub fn create_opcode_map_for(variant: CpuVariant) -> HashMap<OpCode, Vec<Instruction>> {
+    let mut map = HashMap::new();
+
+    for instruction in INSTRUCTION_LOOKUP.values() {
+        map.entry(instruction.opname)
+            .or_insert_with(Vec::new)
+            .push(*instruction);
+    }
+
+    if let Some(extension) = variant.extension() {
+        let mut extra: Vec<&Instruction> = extension.values().collect();
+        extra.sort_by_key(|instruction| instruction.opcode);
+        for instruction in extra {
+            map.entry(instruction.opname)
+                .or_insert_with(Vec::new)
+                .push(*instruction);
+        }
+    }
+
+    map
+}
+
+fn parse_operand(operand: &str) -> (AddressingMode, u32) {
+    if let Some(stripped) = operand.strip_prefix('#') {
+        // Immediate addressing - handle both #$2A and #42 formats
+        let value_str = stripped.trim_start_matches('$');
+        let value = u32::from_str_radix(value_str, 16).unwrap_or_else(|_| {
+            // Try parsing as decimal if hex fails
+            value_str.parse::<u32>().unwrap_or(0)
+        });
+        return (AddressingMode::Immediate, value);
+    }
+
+    let operand = operand.to_uppercase();
+    if let Some(inner) = operand.strip_prefix('(') {
+        // Indirect forms: ($20,X), ($20),Y, ($20) and ($1234), plus the
+        // 65816 stack relative ($03,S),Y
+        if let Some(offset) = inner.strip_suffix(",S),Y") {
+            return (
+                AddressingMode::StackRelativeIndirectY,
+                parse_address(offset).1,
+            );
+        }
+        if let Some(pointer) = inner.strip_suffix(",X)") {
+            let (size, value) = parse_address(pointer);
+            return if size == AddressSize::ZeroPage {
+                (AddressingMode::IndirectX, value)
+            } else {
+                (AddressingMode::AbsoluteIndexedIndirect, value)
+            };
+        }
+        if let Some(pointer) = inner.strip_suffix("),Y") {
+            return (AddressingMode::IndirectY, parse_address(pointer).1);
+        }
+        if let Some(pointer) = inner.strip_suffix(')') {
+            let (size, value) = parse_address(pointer);
+            return if size == AddressSize::ZeroPage {
+                (AddressingMode::ZeroPageIndirect, value)
+            } else {
+                (AddressingMode::Indirect, value)
+            };
+        }
+    }
+    if let Some(inner) = operand.strip_prefix('[') {
+        // 65816 long indirect forms: [$20], [$20],Y and [$1234]
+        if let Some(pointer) = inner.strip_suffix("],Y") {
+            return (
+                AddressingMode::ZeroPageIndirectLongY,
+                parse_address(pointer).1,
+            );
+        }
+        if let Some(pointer) = inner.strip_suffix(']') {
+            let (size, value) = parse_address(pointer);
+            return if size == AddressSize::ZeroPage {
+                (AddressingMode::ZeroPageIndirectLong, value)
+            } else {
+                (AddressingMode::AbsoluteIndirectLong, value)
+            };
+        }
+    }
+
+    // Indexed forms: $20,X, $1234,Y, $123456,X and the 65816 stack relative $03,S
+    let (address, index) = match operand.split_once(',') {
+        Some((address, index)) => (address, Some(index)),
+        None => (operand.as_str(), None),
+    };
+    if let Some(offset) = index.filter(|index| !matches!(*index, "X" | "Y" | "S")) {
+        // Zero page and branch offset for BBR/BBS, e.g. $20,$05, packed as
+        // the two operand bytes in order. MVN/MVP banks use the same form.
+        let zero_page = parse_address(address).1 & 0x00FF;
+        let offset = parse_address(offset).1 & 0x00FF;
+        return (AddressingMode::ZeroPageRelative, zero_page | offset << 8);
+    }
+    let (size, value) = parse_address(address);
+    let mode = match (size, index) {
+        (_, Some("S")) => AddressingMode::StackRelative,
+        (AddressSize::ZeroPage, None) => AddressingMode::ZeroPage,
+        (AddressSize::ZeroPage, Some("X")) => AddressingMode::ZeroPageX,
+        (AddressSize::ZeroPage, Some("Y")) => AddressingMode::ZeroPageY,
+        (AddressSize::Long, None) => AddressingMode::AbsoluteLong,
+        (AddressSize::Long, Some("X")) => AddressingMode::AbsoluteLongX,
+        (_, Some("X")) => AddressingMode::AbsoluteX,
+        (_, Some("Y")) => AddressingMode::AbsoluteY,
+        _ => AddressingMode::Absolute,
+    };
+    (mode, value)
+}
+
+#[derive(PartialEq)]
+enum AddressSize {
+    ZeroPage,
+    Absolute,
+    Long, // 24-bit, for the 65816
+}
+
+// Parses an address and the size it is written in. Only $-prefixed values
+// are treated as zero page or long; bare numbers are always absolute.
+fn parse_address(address: &str) -> (AddressSize, u32) {
+    if let Some(stripped) = address.strip_prefix('$') {
+        let value = u32::from_str_radix(stripped, 16).unwrap_or(0);
+        let size = match value {
+            0..=0xFF => AddressSize::ZeroPage,
+            0x100..=0xFFFF => AddressSize::Absolute,
+            _ => AddressSize::Long,
+        };
+        (size, value)
+    } else {
+        // Try parsing as hex without prefix, then as decimal
+        let value = u16::from_str_radix(address, 16)
+            .unwrap_or_else(|_| address.parse::<u16>().unwrap_or(0));
+        (AddressSize::Absolute, value as u32)
+    }
+}
+
+// The absolute form of a zero page mode, used when an instruction has no
+// zero page encoding for the operand it was given
+fn widen(mode: AddressingMode) -> AddressingMode {
+    match mode {
+        AddressingMode::ZeroPage => AddressingMode::Absolute,
+        AddressingMode::ZeroPageX => AddressingMode::AbsoluteX,
+        AddressingMode::ZeroPageY => AddressingMode::AbsoluteY,
+        AddressingMode::ZeroPageIndirect => AddressingMode::Indirect,
+        AddressingMode::IndirectX => AddressingMode::AbsoluteIndexedIndirect,
+        AddressingMode::ZeroPageRelative => AddressingMode::BlockMove,
+        mode => mode,
+    }
+}
+
+// Whether an immediate operand takes two bytes on the 65816: the instruction
+// must size it by M or X, and the literal must be written with more than two
+// hex digits or exceed a byte. The CPU only reads it as such with the flag clear.
+fn is_wide_immediate(op: OpCode, operand: &str, value: u32) -> bool {
+    let sized = matches!(
+        op,
+        OpCode::LDA
+            | OpCode::LDX
+            | OpCode::LDY
+            | OpCode::ADC
+            | OpCode::SBC
+            | OpCode::AND
+            | OpCode::ORA
+            | OpCode::EOR
+            | OpCode::CMP
+            | OpCode::CPX
+            | OpCode::CPY
+            | OpCode::BIT
+    );
+    let digits = operand.trim_start_matches("#$").len();
+    sized && (value > 0xFF || (operand.starts_with("#$") && digits > 2))
+}
+
+pub fn assemble(source: &str) -> Vec<u8> {
+    assemble_for(source, CpuVariant::default())
+}
+
+/// Assembles `source`, accepting the mnemonics that `variant` implements.
+///
+/// Panics on a mnemonic that `variant` does not implement.
+pub fn assemble_for(source: &str, variant: CpuVariant) -> Vec<u8> {
+    let opcodes = create_opcode_map_for(variant);
+    let mut machine_code = Vec::new();
+
+    for line in source.lines() {
+        let line = line.split(';').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let mnemonic = OpCode::from_str(&parts[0].to_uppercase()).unwrap();
+
+        let Some(instructions) = opcodes.get(&mnemonic) else {
+            panic!("{:?} is not available on {:?}", mnemonic, variant);
+        };
+
+        if parts.len() > 1 {
+            let (mode, value) = parse_operand(parts[1]);
+
+            // Find matching instruction for addressing mode
+            if let Some(instruction) = instructions
+                .iter()
+                .find(|i| i.mode == mode)
+                .or_else(|| instructions.iter().find(|i| i.mode == widen(mode)))
+            {
+                machine_code.push(instruction.opcode);
+
+                let bytes = match instruction.mode {
+                    AddressingMode::Immediate
+                        if variant.is_65816() && is_wide_immediate(mnemonic, parts[1], value) =>
+                    {
+                        3
+                    }
+                    _ => instruction.bytes,
+                };
+                // MVN and MVP are written source bank first but encoded
+                // destination bank first
+                let value = match instruction.mode {
+                    AddressingMode::BlockMove => (value & 0xFF) << 8 | value >> 8,
+                    _ => value,
+                };
+
+                // Add operand bytes
+                match bytes {
+                    2 => machine_code.push(value as u8),
+                    3 => {
+                        machine_code.push((value & 0xFF) as u8);
+                        machine_code.push((value >> 8) as u8);
+                    }
+                    4 => {
+                        machine_code.push((value & 0xFF) as u8);
+                        machine_code.push((value >> 8) as u8);
+                        machine_code.push((value >> 16) as u8);
+                    }
+                    _ => {}
+                }
             }
+        } else if let Some(instruction) = instructions
+            .iter()
+            .find(|i| i.mode == AddressingMode::Implied)
+        {
+            machine_code.push(instruction.opcode);
         }
     }
 
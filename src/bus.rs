@@ -0,0 +1,40 @@
+/// Everything the CPU reads and writes goes through a `Bus`: RAM, ROM and
+/// memory-mapped devices alike. `Memory` is the plain RAM implementation.
+pub trait Bus {
+    fn read(&mut self, address: u16) -> u8;
+
+    fn write(&mut self, address: u16, value: u8);
+
+    /// Reads the opcode of the instruction at `address`. Buses that care,
+    /// such as ones tracing execution, can tell it apart from other reads.
+    fn fetch(&mut self, address: u16) -> u8 {
+        self.read(address)
+    }
+
+    /// Called with the clock cycles the CPU has just spent, so devices on the
+    /// bus can keep time with it. A full instruction's worth may arrive at
+    /// once; `CPU::tick` passes them one at a time.
+    fn tick(&mut self, _cycles: u8) {}
+
+    /// Whether something on the bus is asserting IRQ. The CPU treats this
+    /// like its own IRQ input.
+    fn irq(&self) -> bool {
+        false
+    }
+
+    /// Reads from a 24-bit address, as used by the 65C816. Buses without
+    /// banks see only the low 16 bits.
+    fn read_long(&mut self, address: u32) -> u8 {
+        self.read(address as u16)
+    }
+
+    /// Writes to a 24-bit address, as used by the 65C816.
+    fn write_long(&mut self, address: u32, value: u8) {
+        self.write(address as u16, value);
+    }
+
+    /// Fetches an opcode from a 24-bit address, as used by the 65C816.
+    fn fetch_long(&mut self, address: u32) -> u8 {
+        self.read_long(address)
+    }
+}
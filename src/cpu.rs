@@ -1,172 +1,844 @@
 use crate::assembler::AddressingMode;
-use crate::assembler::INSTRUCTION_LOOKUP;
 use crate::assembler::Instruction;
 use crate::assembler::OpCode;
+use crate::bus::Bus;
 use crate::memory::Memory;
+use crate::random::Xorshift;
+use crate::variant::CpuVariant;
+use std::collections::BTreeSet;
+use std::fmt;
+
+mod cycle;
+mod run;
+mod status;
+mod w65c816;
+
+pub use cycle::{BusAccess, BusCycle};
+pub use run::{RunSummary, StopReason};
+pub use status::StatusFlags;
+pub use w65c816::Registers816;
+
+const NMI_VECTOR: u16 = 0xFFFA;
+const RESET_VECTOR: u16 = 0xFFFC;
+const IRQ_VECTOR: u16 = 0xFFFE; // Shared by IRQ and BRK
+const INTERRUPT_CYCLES: u8 = 7;
+
+/// Why the CPU stopped executing instructions.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum HaltReason {
+    /// A BRK instruction was executed.
+    Brk,
+    /// An RTS was executed with nothing left on the stack to return to.
+    TopLevelRts,
+    /// A KIL/JAM opcode locked up the processor.
+    Jam,
+    /// An STP instruction stopped the clock until the next reset.
+    Stopped,
+    /// The opcode at `pc` is not one the CPU variant implements. PC is left
+    /// on it.
+    IllegalOpcode { pc: u16, opcode: u8 },
+    /// PC reached a breakpoint; the instruction there has not run.
+    Breakpoint { pc: u16 },
+    /// The cycle budget was used up.
+    CycleBudget,
+    /// The jump or branch at `pc` jumped to itself, such as `JMP *`.
+    SelfLoop { pc: u16 },
+    /// An instruction wrote `value` to the exit address.
+    ExitWrite { address: u16, value: u8 },
+}
+
+/// What a call to `execute_instruction` ran.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StepOutcome {
+    /// An instruction, taking this many clock cycles.
+    Executed(u8),
+    /// An interrupt sequence, taking this many clock cycles.
+    Interrupted(u8),
+    /// A single idle cycle while waiting for an interrupt after WAI.
+    Waiting,
+}
+
+impl StepOutcome {
+    /// Clock cycles spent.
+    pub fn cycles(self) -> u8 {
+        match self {
+            StepOutcome::Executed(cycles) | StepOutcome::Interrupted(cycles) => cycles,
+            StepOutcome::Waiting => 1,
+        }
+    }
+}
+
+/// Why `execute_instruction` could not run an instruction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CpuError {
+    /// The opcode at `pc` is not one the CPU variant implements. The CPU is
+    /// halted with PC left on it.
+    IllegalOpcode { pc: u16, opcode: u8 },
+    /// A KIL/JAM opcode locked up the processor until the next reset.
+    Jammed,
+    /// The CPU is halted for the given reason until the next reset, or
+    /// `resume` for a halt condition.
+    Halted(HaltReason),
+}
+
+impl fmt::Display for CpuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CpuError::IllegalOpcode { pc, opcode } => {
+                write!(f, "illegal opcode ${:02X} at ${:04X}", opcode, pc)
+            }
+            CpuError::Jammed => write!(f, "CPU jammed"),
+            CpuError::Halted(reason) => write!(f, "CPU halted by {:?}", reason),
+        }
+    }
+}
+
+impl std::error::Error for CpuError {}
+
+impl From<HaltReason> for CpuError {
+    fn from(reason: HaltReason) -> Self {
+        match reason {
+            HaltReason::IllegalOpcode { pc, opcode } => CpuError::IllegalOpcode { pc, opcode },
+            HaltReason::Jam => CpuError::Jammed,
+            reason => CpuError::Halted(reason),
+        }
+    }
+}
 
-pub struct CPU {
-    pub a: u8,          // Accumulator
-    pub x: u8,          // X Register
-    pub y: u8,          // Y Register
-    pub pc: u16,        // Program Counter
-    pub sp: u8,         // Stack Pointer
-    pub status: u8,     // Status Register
-    pub memory: Memory, // Memory instance
-    pub halted: bool,   // Flag to indicate if CPU execution should stop
+/// Conditions that stop execution.
+///
+/// Everything defaults to off so the CPU behaves like the hardware; hosts
+/// that run bare programs opt into the conditions they use as an exit.
+/// Jams, STP and illegal opcodes always halt, as the CPU cannot go on.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct HaltConditions {
+    /// Halt on BRK instead of taking the software interrupt through $FFFE.
+    pub brk: bool,
+    /// Halt on an RTS issued when the stack is empty, i.e. SP has not moved
+    /// below where it was after the last reset or TXS.
+    pub top_level_rts: bool,
+    /// Halt before running the instruction at any of these addresses.
+    pub breakpoints: BTreeSet<u16>,
+    /// Halt between instructions once `CPU::cycles` reaches this count. The
+    /// count includes the 7 cycles of each reset.
+    pub cycle_budget: Option<u64>,
+    /// Halt when a jump or branch lands on itself, the trap that test suites
+    /// such as Klaus Dormann's use to signal success or failure.
+    pub self_loop: bool,
+    /// Halt after an instruction writes to this address.
+    pub exit_address: Option<u16>,
 }
 
-impl CPU {
-    pub fn new(memory: Memory) -> Self {
+// How an instruction uses the value at its effective address
+enum Operation<B> {
+    Read(fn(&mut CPU<B>, u8)),
+    Modify(fn(&mut CPU<B>, u8) -> u8), // Applied to A in accumulator mode
+    Store(fn(&CPU<B>) -> u8),
+    StoreHigh(fn(&mut CPU<B>) -> u8), // Undocumented stores ANDed with the address high byte + 1
+    Other,
+}
+
+// Derived impls would needlessly require B: Copy
+impl<B> Clone for Operation<B> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<B> Copy for Operation<B> {}
+
+#[allow(clippy::upper_case_acronyms)]
+pub struct CPU<B = Memory> {
+    pub a: u8,               // Accumulator
+    pub x: u8,               // X Register
+    pub y: u8,               // Y Register
+    pub pc: u16,             // Program Counter
+    pub sp: u8,              // Stack Pointer
+    pub status: StatusFlags, // Status Register
+    pub memory: B,           // Memory instance
+    pub variant: CpuVariant,
+    pub waiting: bool, // Set by WAI until an interrupt is requested
+    pub cycles: u64,   // Clock cycles executed since power-on
+    pub halt_conditions: HaltConditions,
+    pub regs816: Registers816, // Extra 65C816 state, unused by the other variants
+    halt_reason: Option<HaltReason>, // Why the CPU is halted, if it is
+    stack_top: u16,            // SP value for an empty stack, used to spot a top-level RTS
+    last_pc: Option<u16>,      // Where the previous instruction started, to spot a self-loop
+    skip_breakpoint: bool,     // Set by `resume` to run the instruction at a breakpoint
+    irq_line: bool,            // Level of the IRQ input, true while asserted
+    irq_pending: bool,         // IRQ requested by `irq`, held until serviced
+    nmi_line: bool,            // Level of the NMI input, true while asserted
+    nmi_pending: bool,         // Latched NMI edge waiting to be serviced
+    page_crossed: bool,        // Whether the last indexed address crossed a page
+    extra_cycles: u8,          // Penalty cycles accrued by the current instruction
+    in_flight: Option<cycle::InFlight>, // Instruction part-way through being ticked
+}
+
+impl<B: Bus> CPU<B> {
+    pub fn new(memory: B) -> Self {
+        Self::with_variant(memory, CpuVariant::default())
+    }
+
+    pub fn with_variant(memory: B, variant: CpuVariant) -> Self {
         Self {
             a: 0,
             x: 0,
             y: 0,       //
             pc: 0x0000, // 16-bit program counter
-            sp: 0xFF,   // 16-bit stack pointer
-            status: 0,
+            sp: 0x00,   // Power-on value; the dummy pushes of `reset` take it to $FD
+            status: StatusFlags::empty(),
             memory,
-            halted: false, // Initialize halted to false
+            variant,
+            waiting: false,
+            cycles: 0,
+            halt_conditions: HaltConditions::default(),
+            regs816: Registers816::default(),
+            halt_reason: None,
+            stack_top: 0x01FF,
+            last_pc: None,
+            skip_breakpoint: false,
+            irq_line: false,
+            irq_pending: false,
+            nmi_line: false,
+            nmi_pending: false,
+            page_crossed: false,
+            extra_cycles: 0,
+            in_flight: None,
         }
     }
 
+    /// Runs the RESET sequence, as after power-on or the RESET line. Like an
+    /// interrupt it takes 7 cycles and goes through the motions of pushing
+    /// PC and status, but the writes are turned into reads, so SP drops by 3
+    /// and the stack is left alone; from power-on that leaves SP at $FD. The
+    /// I flag and bit 5 are set and the 65C02 and 65C816 clear D. Other flags
+    /// keep whatever they held.
     pub fn reset(&mut self) {
-        self.pc = self.memory.read_u16(0xFFFC); // Read reset vector
-        self.sp = 0xFD; // Reset stack pointer
-        self.status = 0x00; // Clear status register
+        for _ in 0..3 {
+            self.memory.read(self.stack_pointer());
+            self.sp = self.sp.wrapping_sub(1);
+        }
+        self.pc = self.read_u16(RESET_VECTOR); // Read reset vector
+        self.status.insert(StatusFlags::UNUSED);
+        self.status.insert(StatusFlags::INTERRUPT_DISABLE);
+        if self.variant.is_cmos() {
+            self.status.remove(StatusFlags::DECIMAL);
+        }
+        if self.variant.is_65816() {
+            self.reset_65816();
+        }
+        self.spend_cycles(INTERRUPT_CYCLES);
+        self.stack_top = self.stack_pointer();
+        self.nmi_pending = false;
+        self.irq_pending = false;
+        self.in_flight = None;
+        self.waiting = false;
+        self.halt_reason = None;
+        self.last_pc = None;
+        self.skip_breakpoint = false;
+    }
+
+    /// Gives A, X, Y, SP and the status register pseudo-random contents drawn
+    /// from `seed`, as they have at power-on. Call it before `reset`, which
+    /// sets up only what the hardware defines; together with
+    /// `Memory::randomize` this catches software that relies on
+    /// uninitialized state.
+    pub fn randomize_registers(&mut self, seed: u64) {
+        let mut rng = Xorshift::new(seed);
+        self.a = rng.next_u8();
+        self.x = rng.next_u8();
+        self.y = rng.next_u8();
+        self.sp = rng.next_u8();
+        self.status = StatusFlags::from_bits(rng.next_u8());
+        self.status.remove(StatusFlags::BREAK); // No B flag in the register
+        self.regs816.b = rng.next_u8();
+    }
+
+    /// Why the CPU is halted, or `None` while it can run.
+    pub fn halt_reason(&self) -> Option<HaltReason> {
+        self.halt_reason
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halt_reason.is_some()
+    }
+
+    /// Clears a halt raised by one of the `HaltConditions` so execution
+    /// carries on from PC, passing over a breakpoint there once. Halts the
+    /// processor itself causes, a jam, STP or an illegal opcode, are left in
+    /// place for a reset to clear.
+    pub fn resume(&mut self) {
+        if matches!(
+            self.halt_reason,
+            Some(HaltReason::Jam | HaltReason::Stopped | HaltReason::IllegalOpcode { .. })
+        ) {
+            return;
+        }
+        self.halt_reason = None;
+        self.last_pc = None;
+        self.skip_breakpoint = true;
+    }
+
+    /// Requests a maskable interrupt, as a device holding IRQ until it is
+    /// serviced would. It is taken at the next instruction boundary with the
+    /// I flag clear, and wakes a CPU waiting after WAI either way.
+    pub fn irq(&mut self) {
+        self.irq_pending = true;
+    }
+
+    /// Requests a non-maskable interrupt, as an edge on the NMI line does.
+    /// It is taken at the next instruction boundary.
+    pub fn nmi(&mut self) {
+        self.nmi_pending = true;
     }
 
-    pub fn execute_instruction(&mut self) {
-        if self.halted {
+    /// Drives the IRQ input. The line is level-triggered: while it stays
+    /// asserted an interrupt is taken before every instruction that runs with
+    /// the I flag clear.
+    pub fn set_irq_line(&mut self, asserted: bool) {
+        self.irq_line = asserted;
+    }
+
+    /// Drives the NMI input. The line is edge-triggered: only the transition
+    /// to asserted latches an interrupt, which is taken before the next
+    /// instruction.
+    pub fn set_nmi_line(&mut self, asserted: bool) {
+        if asserted && !self.nmi_line {
+            self.nmi_pending = true;
+        }
+        self.nmi_line = asserted;
+    }
+
+    // Pushes PC and status, masks IRQs and jumps through `vector`. The B flag
+    // only exists on the pushed copy of the status, set for BRK alone.
+    fn interrupt(&mut self, vector: u16, brk: bool) {
+        if self.variant.is_65816() {
+            self.interrupt_65816(vector, brk);
             return;
         }
+        self.push_u16(self.pc);
+        self.push(self.status_for_push(brk));
+        self.enter_interrupt();
+        self.pc = self.read_u16(vector);
+    }
+
+    // Masks IRQs once the return state is pushed. The 65C02 also leaves
+    // decimal mode so handlers need not CLD first.
+    fn enter_interrupt(&mut self) {
+        self.status.insert(StatusFlags::INTERRUPT_DISABLE);
+        if self.variant.is_cmos() {
+            self.status.remove(StatusFlags::DECIMAL);
+        }
+    }
+
+    // The status byte pushed by BRK, or by a hardware interrupt
+    fn status_for_push(&self, brk: bool) -> u8 {
+        if brk {
+            self.status.pushed_by_instruction()
+        } else {
+            self.status.pushed_by_interrupt()
+        }
+    }
+
+    // Interrupt lines are sampled between instructions, with NMI taking
+    // priority. Returns whether an interrupt sequence was run.
+    fn poll_interrupts(&mut self) -> bool {
+        match self.pending_interrupt() {
+            Some(vector) => {
+                self.interrupt(vector, false);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // WAI ends once either line requests an interrupt. A masked IRQ still
+    // ends it, resuming at the next instruction without being serviced.
+    fn interrupt_requested(&self) -> bool {
+        self.nmi_pending || self.irq_pending || self.irq_asserted()
+    }
+
+    // IRQ is wired-OR: the input line or any device on the bus can pull it
+    fn irq_asserted(&self) -> bool {
+        self.irq_line || self.memory.irq()
+    }
+
+    // Returns the vector of the interrupt to take next, consuming a latched NMI
+    fn pending_interrupt(&mut self) -> Option<u16> {
+        if self.nmi_pending {
+            self.nmi_pending = false;
+            return Some(NMI_VECTOR);
+        }
+        if (self.irq_pending || self.irq_asserted())
+            && !self.status.contains(StatusFlags::INTERRUPT_DISABLE)
+        {
+            self.irq_pending = false;
+            return Some(IRQ_VECTOR);
+        }
+        None
+    }
+
+    /// Runs a single instruction, or a pending interrupt sequence, and reports
+    /// what ran and the clock cycles it took. A CPU waiting after WAI idles
+    /// for a single cycle per call.
+    /// An instruction left part-way through by `tick` is finished instead.
+    ///
+    /// Fails without consuming cycles once the CPU is halted, and with the
+    /// reason for the halt on the instruction that halts it.
+    pub fn execute_instruction(&mut self) -> Result<StepOutcome, CpuError> {
+        self.check_halted()?;
+        if self.in_flight.is_none() {
+            self.check_stop_conditions();
+            self.check_halted()?;
+        }
+        if self.waiting {
+            if !self.interrupt_requested() {
+                self.spend_cycles(1);
+                return Ok(StepOutcome::Waiting);
+            }
+            self.waiting = false;
+        }
+        if let Some(state) = self.in_flight {
+            let mut cycles = 0;
+            while self.in_flight.is_some() && self.tick().is_some() {
+                cycles += 1;
+            }
+            self.check_halted()?;
+            return Ok(match state.is_interrupt() {
+                true => StepOutcome::Interrupted(cycles),
+                false => StepOutcome::Executed(cycles),
+            });
+        }
+        let outcome = self.run_instruction();
+        self.spend_cycles(outcome.cycles());
+        self.check_halted()?;
+        Ok(outcome)
+    }
+
+    // Counts cycles the CPU has run and lets the bus catch up with them
+    fn spend_cycles(&mut self, cycles: u8) {
+        self.cycles += cycles as u64;
+        self.memory.tick(cycles);
+    }
+
+    // Halts on the conditions tested between instructions, before the next
+    // one starts
+    fn check_stop_conditions(&mut self) {
+        let skip_breakpoint = std::mem::take(&mut self.skip_breakpoint);
+        let conditions = &self.halt_conditions;
+        if conditions
+            .cycle_budget
+            .is_some_and(|budget| self.cycles >= budget)
+        {
+            self.halt(HaltReason::CycleBudget);
+        } else if conditions.breakpoints.contains(&self.pc) && !skip_breakpoint {
+            self.halt(HaltReason::Breakpoint { pc: self.pc });
+        } else if conditions.self_loop
+            && !self.waiting
+            && self.last_pc == Some(self.pc)
+            && self.jumps_to_itself()
+        {
+            self.halt(HaltReason::SelfLoop { pc: self.pc });
+        }
+        if !self.waiting {
+            self.last_pc = Some(self.pc);
+        }
+    }
+
+    // Whether the instruction at PC, which just ran and came back to PC, is
+    // a jump or branch rather than, say, a 65C816 block move repeating
+    fn jumps_to_itself(&mut self) -> bool {
         let opcode = self.memory.read(self.pc);
-        self.pc += 1;
-
-        let Instruction {
-            opname: op, mode, ..
-        } = INSTRUCTION_LOOKUP.get(&opcode).unwrap();
-
-        match (op, mode) {
-            (OpCode::LDA, _) => self.lda(mode),
-            (OpCode::LDX, _) => self.ldx(mode),
-            (OpCode::LDY, _) => self.ldy(mode),
-            (OpCode::STA, _) => self.sta(mode),
-            (OpCode::STX, _) => self.stx(mode),
-            (OpCode::STY, _) => self.sty(mode),
-            (OpCode::ADC, _) => self.adc(mode),
-            (OpCode::INX, _) => self.inx(),
-            (OpCode::INY, _) => self.iny(),
-            (OpCode::DEX, _) => self.dex(),
-            (OpCode::DEY, _) => self.dey(),
-            (OpCode::INC, _) => self.inc(mode),
-            (OpCode::DEC, _) => self.dec(mode),
-            (OpCode::SBC, _) => self.sbc(mode),
-            (OpCode::AND, _) => self.and(mode),
-            (OpCode::ORA, _) => self.ora(mode),
-            (OpCode::EOR, _) => self.eor(mode),
-            (OpCode::ASL, mode) if *mode == AddressingMode::Immediate => self.asl_accumulator(),
-            (OpCode::ASL, _) => self.asl(mode),
-            (OpCode::LSR, mode) if *mode == AddressingMode::Immediate => self.lsr_accumulator(),
-            (OpCode::LSR, _) => self.lsr(mode),
-            (OpCode::ROL, mode) if *mode == AddressingMode::Immediate => self.rol_accumulator(),
-            (OpCode::ROL, _) => self.rol(mode),
-            (OpCode::ROR, mode) if *mode == AddressingMode::Immediate => self.ror_accumulator(),
-            (OpCode::ROR, _) => self.ror(mode),
-            (OpCode::BRA, _) => self.branch(true),
-            (OpCode::BCS, _) => self.branch(self.get_carry_flag()),
-            (OpCode::BCC, _) => self.branch(!self.get_carry_flag()), // BCC (Branch if Carry Clear)
-            (OpCode::BMI, _) => self.branch(self.get_negative_flag()), // BMI (Branch if Minus)
-            (OpCode::BPL, _) => self.branch(!self.get_negative_flag()), // BPL (Branch if Plus)
-            (OpCode::BVC, _) => self.branch(!self.get_overflow_flag()), // BVC (Branch if Overflow Clear)
-            (OpCode::BVS, _) => self.branch(self.get_overflow_flag()), // BVS (Branch if Overflow Set)
-            (OpCode::CLC, _) => self.clear_carry(),                    // CLC
-            (OpCode::CLD, _) => self.clear_decimal(),                  // CLD
-            (OpCode::CLI, _) => self.clear_interrupt(),                // CLI
-            (OpCode::CLV, _) => self.clear_overflow(),                 // CLV
-            (OpCode::SEC, _) => self.set_carry(),                      // SEC
-            (OpCode::SED, _) => self.set_decimal(),                    // SED
-            (OpCode::SEI, _) => self.set_interrupt(),                  // SEI
-            (OpCode::BRK, _) => self.brk(),
-            (OpCode::PLP, _) => self.plp(),
-            (OpCode::PLA, _) => self.pla(),
-            (OpCode::TXS, _) => self.txs(),
-            (OpCode::TYA, _) => self.tya(),
-            (OpCode::TSX, _) => self.tsx(),
-            (OpCode::TAY, _) => self.tay(),
-            (OpCode::TXA, _) => self.txa(),
-            (OpCode::TAX, _) => self.tax(),
-            (OpCode::RTS, _) => self.rts(),
-            (OpCode::PHP, _) => self.php(),
-            (OpCode::PHA, _) => self.pha(),
-            (OpCode::CMP, _) => self.cmp(),
-            (OpCode::BEQ, _) => self.beq(),
-            _ => {
-                println!(
-                    "Opcode {:02X} at address {:04X} not implemented",
-                    opcode,
-                    self.pc - 1
-                );
-                self.halted = true;
+        match self.variant.instruction(opcode).map(|i| i.opname) {
+            Some(OpCode::JMP | OpCode::JML | OpCode::BRL) => true,
+            Some(op) => self.branch_condition(op).is_some(),
+            None => false,
+        }
+    }
+
+    // Halts when a program signals completion by writing to the exit address
+    fn watch_write(&mut self, address: u16, value: u8) {
+        if self.halt_conditions.exit_address == Some(address) {
+            self.halt(HaltReason::ExitWrite { address, value });
+        }
+    }
+
+    fn check_halted(&self) -> Result<(), CpuError> {
+        match self.halt_reason {
+            Some(reason) => Err(reason.into()),
+            None => Ok(()),
+        }
+    }
+
+    // Runs a pending interrupt sequence, or else the next instruction, in one
+    // go
+    fn run_instruction(&mut self) -> StepOutcome {
+        if self.poll_interrupts() {
+            return StepOutcome::Interrupted(self.interrupt_cycles());
+        }
+        self.extra_cycles = 0;
+        let base_cycles = self.step();
+        StepOutcome::Executed(base_cycles + self.extra_cycles)
+    }
+
+    fn halt(&mut self, reason: HaltReason) {
+        self.halt_reason = Some(reason);
+    }
+
+    // Looks up an opcode just fetched from PC - 1. One the variant does not
+    // implement halts the CPU with PC moved back onto it.
+    fn decode(&mut self, opcode: u8) -> Option<&'static Instruction> {
+        let instruction = self.variant.instruction(opcode);
+        if instruction.is_none() {
+            self.pc = self.pc.wrapping_sub(1);
+            self.halt(HaltReason::IllegalOpcode {
+                pc: self.pc,
+                opcode,
+            });
+        }
+        instruction
+    }
+
+    // Executes the instruction at PC and returns its base cycle count
+    fn step(&mut self) -> u8 {
+        if self.variant.is_65816() {
+            return self.step_65816();
+        }
+        let opcode = self.memory.fetch(self.pc);
+        self.pc = self.pc.wrapping_add(1);
+
+        let Some(Instruction {
+            opname: op,
+            mode,
+            cycles,
+            ..
+        }) = self.decode(opcode)
+        else {
+            return 0;
+        };
+
+        match Self::operation(*op, mode) {
+            Operation::Read(apply) => {
+                let value = self.get_operand(mode);
+                if self.decimal_cycle(*op) {
+                    self.extra_cycles += 1;
+                }
+                apply(self, value);
+            }
+            Operation::Modify(apply) if *mode == AddressingMode::Implied => {
+                self.a = apply(self, self.a);
+            }
+            Operation::Modify(apply) => {
+                let addr = self.get_operand_address(mode);
+                if self.page_crossed && !self.modify_always_fixes(*op) {
+                    self.extra_cycles += 1;
+                }
+                let value = self.mem_read(addr);
+                let result = apply(self, value);
+                self.mem_write(addr, result);
+            }
+            Operation::Store(register) => {
+                let addr = self.get_operand_address(mode);
+                self.mem_write(addr, register(self));
+            }
+            Operation::StoreHigh(register) => {
+                let addr = self.get_operand_address(mode);
+                let index = match mode {
+                    AddressingMode::AbsoluteX => self.x,
+                    _ => self.y,
+                };
+                let base = addr.wrapping_sub(index as u16);
+                let value = register(self);
+                let (addr, value) = Self::high_byte_store(base, addr, value);
+                self.mem_write(addr, value);
             }
+            Operation::Other => self.execute_other(*op, mode),
+        }
+        *cycles
+    }
+
+    // Maps the instructions that work on a single operand value to the
+    // function implementing them.
+    fn operation(op: OpCode, mode: &AddressingMode) -> Operation<B> {
+        match op {
+            OpCode::LDA => Operation::Read(Self::load_a),
+            OpCode::LDX => Operation::Read(Self::load_x),
+            OpCode::LDY => Operation::Read(Self::load_y),
+            OpCode::ADC => Operation::Read(Self::adc),
+            OpCode::SBC => Operation::Read(Self::sbc),
+            OpCode::AND => Operation::Read(Self::and),
+            OpCode::ORA => Operation::Read(Self::ora),
+            OpCode::EOR => Operation::Read(Self::eor),
+            OpCode::CMP => Operation::Read(Self::cmp),
+            OpCode::CPX => Operation::Read(Self::cpx),
+            OpCode::CPY => Operation::Read(Self::cpy),
+            OpCode::BIT if *mode == AddressingMode::Immediate => {
+                Operation::Read(Self::bit_immediate)
+            }
+            OpCode::BIT => Operation::Read(Self::bit),
+            OpCode::ASL => Operation::Modify(Self::asl),
+            OpCode::LSR => Operation::Modify(Self::lsr),
+            OpCode::ROL => Operation::Modify(Self::rol),
+            OpCode::ROR => Operation::Modify(Self::ror),
+            OpCode::INC => Operation::Modify(Self::inc),
+            OpCode::DEC => Operation::Modify(Self::dec),
+            OpCode::STA => Operation::Store(|cpu| cpu.a),
+            OpCode::STX => Operation::Store(|cpu| cpu.x),
+            OpCode::STY => Operation::Store(|cpu| cpu.y),
+            OpCode::STZ => Operation::Store(|_| 0),
+            OpCode::RMB0 => Operation::Modify(|_, value| value & !0x01),
+            OpCode::RMB1 => Operation::Modify(|_, value| value & !0x02),
+            OpCode::RMB2 => Operation::Modify(|_, value| value & !0x04),
+            OpCode::RMB3 => Operation::Modify(|_, value| value & !0x08),
+            OpCode::RMB4 => Operation::Modify(|_, value| value & !0x10),
+            OpCode::RMB5 => Operation::Modify(|_, value| value & !0x20),
+            OpCode::RMB6 => Operation::Modify(|_, value| value & !0x40),
+            OpCode::RMB7 => Operation::Modify(|_, value| value & !0x80),
+            OpCode::SMB0 => Operation::Modify(|_, value| value | 0x01),
+            OpCode::SMB1 => Operation::Modify(|_, value| value | 0x02),
+            OpCode::SMB2 => Operation::Modify(|_, value| value | 0x04),
+            OpCode::SMB3 => Operation::Modify(|_, value| value | 0x08),
+            OpCode::SMB4 => Operation::Modify(|_, value| value | 0x10),
+            OpCode::SMB5 => Operation::Modify(|_, value| value | 0x20),
+            OpCode::SMB6 => Operation::Modify(|_, value| value | 0x40),
+            OpCode::SMB7 => Operation::Modify(|_, value| value | 0x80),
+            OpCode::TSB => Operation::Modify(Self::tsb),
+            OpCode::TRB => Operation::Modify(Self::trb),
+            OpCode::NOP if *mode != AddressingMode::Implied => Operation::Read(|_, _| {}),
+            OpCode::SLO => Operation::Modify(Self::slo),
+            OpCode::RLA => Operation::Modify(Self::rla),
+            OpCode::SRE => Operation::Modify(Self::sre),
+            OpCode::RRA => Operation::Modify(Self::rra),
+            OpCode::DCP => Operation::Modify(Self::dcp),
+            OpCode::ISC => Operation::Modify(Self::isc),
+            OpCode::LAX if *mode == AddressingMode::Immediate => Operation::Read(Self::lxa),
+            OpCode::LAX => Operation::Read(Self::lax),
+            OpCode::SAX => Operation::Store(|cpu| cpu.a & cpu.x),
+            OpCode::ANC => Operation::Read(Self::anc),
+            OpCode::ALR => Operation::Read(Self::alr),
+            OpCode::ARR => Operation::Read(Self::arr),
+            OpCode::AXS => Operation::Read(Self::axs),
+            OpCode::XAA => Operation::Read(Self::xaa),
+            OpCode::LAS => Operation::Read(Self::las),
+            OpCode::AHX => Operation::StoreHigh(|cpu| cpu.a & cpu.x),
+            OpCode::SHX => Operation::StoreHigh(|cpu| cpu.x),
+            OpCode::SHY => Operation::StoreHigh(|cpu| cpu.y),
+            OpCode::TAS => Operation::StoreHigh(|cpu| {
+                cpu.sp = cpu.a & cpu.x;
+                cpu.sp
+            }),
+            _ => Operation::Other,
+        }
+    }
+
+    // The 65C02 spends an extra cycle correcting a decimal ADC or SBC
+    fn decimal_cycle(&self, op: OpCode) -> bool {
+        self.variant.is_cmos()
+            && matches!(op, OpCode::ADC | OpCode::SBC)
+            && self.decimal_mode_active()
+    }
+
+    // Whether an indexed read-modify-write spends the high byte fix-up cycle
+    // even when no page is crossed. The 65C02 only does so for INC and DEC.
+    fn modify_always_fixes(&self, op: OpCode) -> bool {
+        !self.variant.is_cmos() || matches!(op, OpCode::INC | OpCode::DEC)
+    }
+
+    // Implied, stack and control-flow instructions
+    fn execute_other(&mut self, op: OpCode, mode: &AddressingMode) {
+        if let Some(taken) = self.branch_condition(op) {
+            self.branch(taken);
+            return;
+        }
+        if let Some((bit, set)) = Self::bit_branch(op) {
+            let addr = self.fetch() as u16;
+            let value = self.mem_read(addr);
+            self.branch((value >> bit) & 1 == set as u8);
+            return;
+        }
+        match op {
+            OpCode::INX => self.inx(),
+            OpCode::INY => self.iny(),
+            OpCode::DEX => self.dex(),
+            OpCode::DEY => self.dey(),
+            OpCode::CLC => self.clear_carry(),     // CLC
+            OpCode::CLD => self.clear_decimal(),   // CLD
+            OpCode::CLI => self.clear_interrupt(), // CLI
+            OpCode::CLV => self.clear_overflow(),  // CLV
+            OpCode::SEC => self.set_carry(),       // SEC
+            OpCode::SED => self.set_decimal(),     // SED
+            OpCode::SEI => self.set_interrupt(),   // SEI
+            OpCode::BRK => self.brk(),
+            OpCode::PLP => self.plp(),
+            OpCode::PLA => self.pla(),
+            OpCode::TXS => self.txs(),
+            OpCode::TYA => self.tya(),
+            OpCode::TSX => self.tsx(),
+            OpCode::TAY => self.tay(),
+            OpCode::TXA => self.txa(),
+            OpCode::TAX => self.tax(),
+            OpCode::RTS => self.rts(),
+            OpCode::PHP => self.php(),
+            OpCode::PHA => self.pha(),
+            OpCode::PHX => self.push(self.x),
+            OpCode::PHY => self.push(self.y),
+            OpCode::PLX => self.plx(),
+            OpCode::PLY => self.ply(),
+            OpCode::JMP => self.jmp(mode),
+            OpCode::JSR => self.jsr(),
+            OpCode::RTI => self.rti(),
+            OpCode::NOP => {}
+            OpCode::KIL => self.halt(HaltReason::Jam),
+            OpCode::WAI => self.waiting = true,
+            OpCode::STP => self.halt(HaltReason::Stopped),
+            _ => unreachable!("{:?} operates on a value", op),
         }
     }
 
-    fn beq(&mut self) {
-        let offset = self.memory.read(self.pc) as i8;
-        self.pc += 1;
-        if self.get_zero_flag() {
-            let jump_addr = ((self.pc as i32) + (offset as i32)) as u16;
-            self.pc = jump_addr;
+    // Whether a branch instruction is taken, or None for anything else
+    fn branch_condition(&self, op: OpCode) -> Option<bool> {
+        match op {
+            OpCode::BRA => Some(true),
+            OpCode::BCS => Some(self.status.contains(StatusFlags::CARRY)),
+            OpCode::BCC => Some(!self.status.contains(StatusFlags::CARRY)), // BCC (Branch if Carry Clear)
+            OpCode::BMI => Some(self.status.contains(StatusFlags::NEGATIVE)), // BMI (Branch if Minus)
+            OpCode::BPL => Some(!self.status.contains(StatusFlags::NEGATIVE)), // BPL (Branch if Plus)
+            OpCode::BVC => Some(!self.status.contains(StatusFlags::OVERFLOW)), // BVC (Branch if Overflow Clear)
+            OpCode::BVS => Some(self.status.contains(StatusFlags::OVERFLOW)), // BVS (Branch if Overflow Set)
+            OpCode::BEQ => Some(self.status.contains(StatusFlags::ZERO)), // BEQ (Branch if Equal)
+            OpCode::BNE => Some(!self.status.contains(StatusFlags::ZERO)), // BNE (Branch if Not Equal)
+            _ => None,
         }
     }
 
-    fn cmp(&mut self) {
-        let value = self.memory.read(self.pc);
-        self.pc += 1;
-        let result = self.a.wrapping_sub(value);
-        self.set_carry_flag(self.a >= value);
-        self.set_zero_flag(result);
-        self.set_negative_flag(result);
+    // The bit tested by BBR/BBS, and whether the branch is taken when it is set
+    fn bit_branch(op: OpCode) -> Option<(u8, bool)> {
+        match op {
+            OpCode::BBR0 => Some((0, false)),
+            OpCode::BBR1 => Some((1, false)),
+            OpCode::BBR2 => Some((2, false)),
+            OpCode::BBR3 => Some((3, false)),
+            OpCode::BBR4 => Some((4, false)),
+            OpCode::BBR5 => Some((5, false)),
+            OpCode::BBR6 => Some((6, false)),
+            OpCode::BBR7 => Some((7, false)),
+            OpCode::BBS0 => Some((0, true)),
+            OpCode::BBS1 => Some((1, true)),
+            OpCode::BBS2 => Some((2, true)),
+            OpCode::BBS3 => Some((3, true)),
+            OpCode::BBS4 => Some((4, true)),
+            OpCode::BBS5 => Some((5, true)),
+            OpCode::BBS6 => Some((6, true)),
+            OpCode::BBS7 => Some((7, true)),
+            _ => None,
+        }
+    }
+
+    fn cmp(&mut self, value: u8) {
+        self.compare(self.a, value);
+    }
+
+    fn cpx(&mut self, value: u8) {
+        self.compare(self.x, value);
+    }
+
+    fn cpy(&mut self, value: u8) {
+        self.compare(self.y, value);
+    }
+
+    fn compare(&mut self, register: u8, value: u8) {
+        let result = register.wrapping_sub(value);
+        self.status.set(StatusFlags::CARRY, register >= value);
+        self.update_zero_and_negative_flags(result);
+    }
+
+    fn bit(&mut self, value: u8) {
+        self.status.set(StatusFlags::ZERO, self.a & value == 0);
+        self.status.set(StatusFlags::NEGATIVE, value & 0x80 != 0);
+        self.status
+            .set(StatusFlags::OVERFLOW, value & 0b0100_0000 != 0);
+    }
+
+    // BIT #imm has no memory operand whose bits 6 and 7 could be copied
+    fn bit_immediate(&mut self, value: u8) {
+        self.status.set(StatusFlags::ZERO, self.a & value == 0);
+    }
+
+    // TSB and TRB set Z like BIT, then set or clear the bits of A in memory
+    fn tsb(&mut self, value: u8) -> u8 {
+        self.status.set(StatusFlags::ZERO, self.a & value == 0);
+        value | self.a
+    }
+
+    fn trb(&mut self, value: u8) -> u8 {
+        self.status.set(StatusFlags::ZERO, self.a & value == 0);
+        value & !self.a
+    }
+
+    fn jmp(&mut self, mode: &AddressingMode) {
+        self.pc = self.get_operand_address(mode);
+    }
+
+    fn jsr(&mut self) {
+        let target = self.fetch_u16();
+        // The return address pushed is the last byte of the JSR instruction
+        self.push_u16(self.pc.wrapping_sub(1));
+        self.pc = target;
+    }
+
+    fn rti(&mut self) {
+        self.status = StatusFlags::pulled(self.pull());
+        self.pc = self.pull_u16();
     }
 
     fn tax(&mut self) {
         self.x = self.a;
-        self.set_zero_flag(self.x);
-        self.set_negative_flag(self.x);
+        self.update_zero_and_negative_flags(self.x);
     }
     fn tay(&mut self) {
         self.y = self.a;
-        self.set_zero_flag(self.y);
-        self.set_negative_flag(self.y);
+        self.update_zero_and_negative_flags(self.y);
     }
     fn txa(&mut self) {
         self.a = self.x;
-        self.set_zero_flag(self.a);
-        self.set_negative_flag(self.a);
+        self.update_zero_and_negative_flags(self.a);
     }
     fn tya(&mut self) {
         self.a = self.y;
-        self.set_zero_flag(self.a);
-        self.set_negative_flag(self.a);
+        self.update_zero_and_negative_flags(self.a);
     }
     fn tsx(&mut self) {
         self.x = self.sp;
-        self.set_zero_flag(self.x);
-        self.set_negative_flag(self.x);
+        self.update_zero_and_negative_flags(self.x);
     }
 
     fn rts(&mut self) {
-        self.halted = true;
+        if self.halt_conditions.top_level_rts && self.stack_pointer() >= self.stack_top {
+            self.halt(HaltReason::TopLevelRts);
+            return;
+        }
+        // JSR pushed the address of its last byte, so step past it
+        self.pc = self.pull_u16().wrapping_add(1);
     }
 
     fn pla(&mut self) {
         self.a = self.pull();
-        self.set_zero_flag(self.a);
-        self.set_negative_flag(self.a);
+        self.update_zero_and_negative_flags(self.a);
+    }
+
+    fn plx(&mut self) {
+        self.x = self.pull();
+        self.update_zero_and_negative_flags(self.x);
+    }
+
+    fn ply(&mut self) {
+        self.y = self.pull();
+        self.update_zero_and_negative_flags(self.y);
     }
 
     fn txs(&mut self) {
         self.sp = self.x;
+        self.stack_top = self.stack_pointer();
     }
 
     fn pha(&mut self) {
@@ -174,278 +846,357 @@ impl CPU {
     }
 
     fn php(&mut self) {
-        self.push(self.status | 0b0011_0000);
+        self.push(self.status.pushed_by_instruction());
     }
 
     fn plp(&mut self) {
-        self.status = (self.pull() & 0b1110_1111) | 0b0010_0000;
+        self.status = StatusFlags::pulled(self.pull());
     }
 
     fn brk(&mut self) {
-        self.set_break_flag(true);
-        self.halted = true;
+        if self.halt_conditions.brk {
+            self.halt(HaltReason::Brk);
+            return;
+        }
+        // BRK is two bytes long; the byte after the opcode is padding
+        self.pc = self.pc.wrapping_add(1);
+        self.interrupt(IRQ_VECTOR, true);
     }
 
-    fn adc(&mut self, mode: &AddressingMode) {
-        let value = self.get_operand(mode);
-        let carry = self.get_carry_flag() as u16;
-        let sum = self.a as u16 + value as u16 + carry;
+    fn adc(&mut self, value: u8) {
+        self.add_with_carry(value, false);
+    }
 
-        self.set_carry_flag(sum > 0xFF);
+    // SBC is ADC of the operand's ones' complement: A + !M + C == A - M - (1 - C).
+    fn sbc(&mut self, value: u8) {
+        self.add_with_carry(!value, true);
+    }
+
+    // Shared ALU for ADC and SBC. The binary sum drives every flag except in
+    // decimal addition, where the NMOS part derives N, V and C from the
+    // partially adjusted result. Only the decimal adjustment depends on the
+    // direction of the operation.
+    fn add_with_carry(&mut self, operand: u8, subtract: bool) {
+        let carry = self.status.contains(StatusFlags::CARRY) as u16;
+        let sum = self.a as u16 + operand as u16 + carry;
         let result = sum as u8;
-        self.set_overflow_flag(((self.a ^ result) & (value ^ result) & 0x80) != 0);
 
-        self.a = result;
-        self.update_zero_and_negative_flags(self.a);
-    }
+        self.status.set(StatusFlags::CARRY, sum > 0xFF);
+        self.status.set(
+            StatusFlags::OVERFLOW,
+            ((self.a ^ result) & (operand ^ result) & 0x80) != 0,
+        );
+        self.update_zero_and_negative_flags(result);
 
-    fn and(&mut self, mode: &AddressingMode) {
-        let value = self.get_operand(mode);
-        self.a &= value;
-        self.update_zero_and_negative_flags(self.a);
-    }
+        if !self.decimal_mode_active() {
+            self.a = result;
+            return;
+        }
 
-    fn ora(&mut self, mode: &AddressingMode) {
-        let value = self.get_operand(mode);
-        self.a |= value;
-        self.update_zero_and_negative_flags(self.a);
+        let low_sum = (self.a & 0x0F) as u16 + (operand & 0x0F) as u16 + carry;
+        self.a = match (subtract, self.variant.is_cmos()) {
+            (false, _) => self.decimal_adjust_add(operand, low_sum),
+            (true, false) => Self::decimal_adjust_subtract(sum, low_sum),
+            (true, true) => Self::decimal_adjust_subtract_cmos(sum, low_sum),
+        };
+        if self.variant.is_cmos() {
+            // The 65C02 derives N and Z from the corrected result
+            self.update_zero_and_negative_flags(self.a);
+        }
     }
 
-    fn eor(&mut self, mode: &AddressingMode) {
-        let value = self.get_operand(mode);
-        self.a ^= value;
-        self.update_zero_and_negative_flags(self.a);
+    fn decimal_adjust_add(&mut self, operand: u8, low_sum: u16) -> u8 {
+        let mut lo = low_sum;
+        if lo > 0x09 {
+            lo += 0x06;
+        }
+        let mut hi = (self.a >> 4) as u16 + (operand >> 4) as u16 + (lo > 0x0F) as u16;
+
+        let intermediate = ((hi << 4) & 0xF0) as u8;
+        self.status
+            .set(StatusFlags::NEGATIVE, intermediate & 0x80 != 0);
+        self.status.set(
+            StatusFlags::OVERFLOW,
+            ((self.a ^ intermediate) & 0x80) != 0 && ((self.a ^ operand) & 0x80) == 0,
+        );
+
+        if hi > 0x09 {
+            hi += 0x06;
+        }
+        self.status.set(StatusFlags::CARRY, hi > 0x0F);
+        ((hi << 4) | (lo & 0x0F)) as u8
     }
 
-    fn asl(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
-        self.set_carry_flag(value & 0x80 != 0);
-        let result = value << 1;
-        self.mem_write(addr, result);
-        self.update_zero_and_negative_flags(result);
+    // A nibble that produced no carry borrowed during the subtraction and
+    // needs 6 taken off; the borrow itself is already part of the binary sum.
+    fn decimal_adjust_subtract(sum: u16, low_sum: u16) -> u8 {
+        let mut lo = sum & 0x0F;
+        let mut hi = (sum >> 4) & 0x0F;
+        if low_sum < 0x10 {
+            lo = lo.wrapping_sub(0x06) & 0x0F;
+        }
+        if sum < 0x100 {
+            hi = hi.wrapping_sub(0x06) & 0x0F;
+        }
+        ((hi << 4) | lo) as u8
     }
 
-    fn asl_accumulator(&mut self) {
-        self.set_carry_flag(self.a & 0x80 != 0);
-        self.a <<= 1;
-        self.update_zero_and_negative_flags(self.a);
+    // The 65C02 subtracts the corrections from the whole byte, so a borrow
+    // out of the low nibble propagates into the high one.
+    fn decimal_adjust_subtract_cmos(sum: u16, low_sum: u16) -> u8 {
+        let mut result = sum as u8;
+        if sum < 0x100 {
+            result = result.wrapping_sub(0x60);
+        }
+        if low_sum < 0x10 {
+            result = result.wrapping_sub(0x06);
+        }
+        result
     }
 
-    fn lsr(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
-        self.set_carry_flag(value & 0x01 != 0);
-        let result = value >> 1;
-        self.mem_write(addr, result);
-        self.update_zero_and_negative_flags(result);
+    // Undocumented read-modify-write instructions run a shift or increment on
+    // memory, then feed the result to an accumulator operation.
+    fn slo(&mut self, value: u8) -> u8 {
+        let result = self.asl(value);
+        self.ora(result);
+        result
     }
 
-    fn lsr_accumulator(&mut self) {
-        self.set_carry_flag(self.a & 0x01 != 0);
-        self.a >>= 1;
-        self.update_zero_and_negative_flags(self.a);
+    fn rla(&mut self, value: u8) -> u8 {
+        let result = self.rol(value);
+        self.and(result);
+        result
     }
 
-    fn rol(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
-        let carry = self.get_carry_flag() as u8;
-        self.set_carry_flag(value & 0x80 != 0);
-        let result = (value << 1) | carry;
-        self.mem_write(addr, result);
-        self.update_zero_and_negative_flags(result);
+    fn sre(&mut self, value: u8) -> u8 {
+        let result = self.lsr(value);
+        self.eor(result);
+        result
     }
 
-    fn rol_accumulator(&mut self) {
-        let carry = self.get_carry_flag() as u8;
-        self.set_carry_flag(self.a & 0x80 != 0);
-        self.a = (self.a << 1) | carry;
-        self.update_zero_and_negative_flags(self.a);
+    fn rra(&mut self, value: u8) -> u8 {
+        let result = self.ror(value);
+        self.adc(result);
+        result
     }
 
-    fn ror(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
-        let carry = self.get_carry_flag() as u8;
-        self.set_carry_flag(value & 0x01 != 0);
-        let result = (value >> 1) | (carry << 7);
-        self.mem_write(addr, result);
-        self.update_zero_and_negative_flags(result);
+    fn dcp(&mut self, value: u8) -> u8 {
+        let result = value.wrapping_sub(1);
+        self.cmp(result);
+        result
     }
 
-    fn ror_accumulator(&mut self) {
-        let carry = self.get_carry_flag() as u8;
-        self.set_carry_flag(self.a & 0x01 != 0);
-        self.a = (self.a >> 1) | (carry << 7);
-        self.update_zero_and_negative_flags(self.a);
+    fn isc(&mut self, value: u8) -> u8 {
+        let result = value.wrapping_add(1);
+        self.sbc(result);
+        result
     }
 
-    fn branch(&mut self, condition: bool) {
-        if condition {
-            let offset = self.memory.read(self.pc) as i8; // Read signed offset
-            self.pc += 1; // Increment program counter
-            self.pc = ((self.pc as i32) + (offset as i32)) as u16; // Calculate new address
-        } else {
-            self.pc += 1; // Skip the offset byte
-        }
+    fn lax(&mut self, value: u8) {
+        self.load_a(value);
+        self.x = value;
     }
 
-    fn clear_carry(&mut self) {
-        self.set_carry_flag(false);
+    // LAX #imm is unstable on hardware; the commonly observed magic constant
+    // is used for the bits of A that leak into the result.
+    fn lxa(&mut self, value: u8) {
+        self.lax((self.a | self.magic_constant()) & value);
     }
 
-    fn clear_decimal(&mut self) {
-        self.set_decimal_flag(false);
+    fn xaa(&mut self, value: u8) {
+        self.load_a((self.a | self.magic_constant()) & self.x & value);
     }
 
-    fn clear_interrupt(&mut self) {
-        self.set_interrupt_disable_flag(false);
+    // NES consoles are consistently observed with every bit of A leaking
+    fn magic_constant(&self) -> u8 {
+        match self.variant {
+            CpuVariant::Ricoh2A03 => 0xFF,
+            _ => 0xEE,
+        }
     }
 
-    fn clear_overflow(&mut self) {
-        self.set_overflow_flag(false);
+    fn anc(&mut self, value: u8) {
+        self.and(value);
+        self.status.set(
+            StatusFlags::CARRY,
+            self.status.contains(StatusFlags::NEGATIVE),
+        );
     }
 
-    fn set_carry(&mut self) {
-        self.set_carry_flag(true);
+    fn alr(&mut self, value: u8) {
+        self.and(value);
+        self.a = self.lsr(self.a);
     }
 
-    fn set_decimal(&mut self) {
-        self.set_decimal_flag(true);
-    }
+    // AND followed by ROR A, with C and V taken from bits 6 and 5 of the
+    // result. In decimal mode the NMOS part also applies a BCD correction.
+    fn arr(&mut self, value: u8) {
+        let and = self.a & value;
+        let carry = self.status.contains(StatusFlags::CARRY) as u8;
+        let mut result = (and >> 1) | (carry << 7);
+        self.update_zero_and_negative_flags(result);
 
-    fn set_interrupt(&mut self) {
-        self.set_interrupt_disable_flag(true);
+        if !self.decimal_mode_active() {
+            self.status.set(StatusFlags::CARRY, result & 0x40 != 0);
+            self.status.set(
+                StatusFlags::OVERFLOW,
+                ((result >> 6) ^ (result >> 5)) & 0x01 != 0,
+            );
+            self.a = result;
+            return;
+        }
+
+        self.status
+            .set(StatusFlags::OVERFLOW, (and ^ result) & 0x40 != 0);
+        if (and & 0x0F) + (and & 0x01) > 0x05 {
+            result = (result & 0xF0) | (result.wrapping_add(0x06) & 0x0F);
+        }
+        let high_adjust = (and & 0xF0) as u16 + (and & 0x10) as u16 > 0x50;
+        if high_adjust {
+            result = result.wrapping_add(0x60);
+        }
+        self.status.set(StatusFlags::CARRY, high_adjust);
+        self.a = result;
     }
 
-    pub fn lda(&mut self, mode: &AddressingMode) {
-        let value = self.get_operand(mode);
-        self.a = value;
-        self.set_zero_flag(self.a);
-        self.set_negative_flag(self.a);
+    // X = (A & X) - value, setting flags like CMP and ignoring decimal mode
+    fn axs(&mut self, value: u8) {
+        let and = self.a & self.x;
+        self.x = and.wrapping_sub(value);
+        self.status.set(StatusFlags::CARRY, and >= value);
+        self.update_zero_and_negative_flags(self.x);
     }
 
-    pub fn ldx(&mut self, mode: &AddressingMode) {
-        let value = self.get_operand(mode);
-        self.x = value;
-        self.set_zero_flag(self.x);
-        self.set_negative_flag(self.x);
+    fn las(&mut self, value: u8) {
+        let result = value & self.sp;
+        self.sp = result;
+        self.x = result;
+        self.load_a(result);
     }
 
-    pub fn ldy(&mut self, mode: &AddressingMode) {
-        let value = self.get_operand(mode);
-        self.y = value;
-        self.set_zero_flag(self.y);
-        self.set_negative_flag(self.y);
+    // The value stored is ANDed with the high byte of the unindexed address
+    // plus one. When indexing crosses a page, that value also replaces the
+    // high byte of the address written to.
+    fn high_byte_store(base: u16, addr: u16, value: u8) -> (u16, u8) {
+        let value = value & ((base >> 8) as u8).wrapping_add(1);
+        if base & 0xFF00 != addr & 0xFF00 {
+            (((value as u16) << 8) | (addr & 0x00FF), value)
+        } else {
+            (addr, value)
+        }
     }
 
-    pub fn sta(&mut self, mode: &AddressingMode) {
-        self.memory.write(self.get_operand_address(mode), self.a);
+    fn decimal_mode_active(&self) -> bool {
+        self.variant.has_decimal_mode() && self.status.contains(StatusFlags::DECIMAL)
     }
 
-    pub fn stx(&mut self, mode: &AddressingMode) {
-        self.memory.write(self.get_operand_address(mode), self.x);
+    fn and(&mut self, value: u8) {
+        self.a &= value;
+        self.update_zero_and_negative_flags(self.a);
     }
 
-    pub fn sty(&mut self, mode: &AddressingMode) {
-        self.memory.write(self.get_operand_address(mode), self.y);
+    fn ora(&mut self, value: u8) {
+        self.a |= value;
+        self.update_zero_and_negative_flags(self.a);
     }
 
-    fn set_flag(&mut self, mask: u8) {
-        self.status |= mask;
+    fn eor(&mut self, value: u8) {
+        self.a ^= value;
+        self.update_zero_and_negative_flags(self.a);
     }
 
-    fn clear_flag(&mut self, mask: u8) {
-        self.status &= !mask;
+    fn asl(&mut self, value: u8) -> u8 {
+        self.status.set(StatusFlags::CARRY, value & 0x80 != 0);
+        let result = value << 1;
+        self.update_zero_and_negative_flags(result);
+        result
     }
 
-    pub fn set_negative_flag(&mut self, value: u8) {
-        if value & 0x80 != 0 {
-            self.set_flag(0b1000_0000); // Set N flag
-        } else {
-            self.clear_flag(0b1000_0000); // Clear N flag
-        }
+    fn lsr(&mut self, value: u8) -> u8 {
+        self.status.set(StatusFlags::CARRY, value & 0x01 != 0);
+        let result = value >> 1;
+        self.update_zero_and_negative_flags(result);
+        result
     }
 
-    pub fn set_overflow_flag(&mut self, overflow: bool) {
-        if overflow {
-            self.set_flag(0b0100_0000); // Set V flag
-        } else {
-            self.clear_flag(0b0100_0000); // Clear V flag
-        }
+    fn rol(&mut self, value: u8) -> u8 {
+        let carry = self.status.contains(StatusFlags::CARRY) as u8;
+        self.status.set(StatusFlags::CARRY, value & 0x80 != 0);
+        let result = (value << 1) | carry;
+        self.update_zero_and_negative_flags(result);
+        result
     }
 
-    pub fn set_break_flag(&mut self, break_flag: bool) {
-        if break_flag {
-            self.set_flag(0b0001_0000); // Set B flag
-        } else {
-            self.clear_flag(0b0001_0000); // Clear B flag
-        }
+    fn ror(&mut self, value: u8) -> u8 {
+        let carry = self.status.contains(StatusFlags::CARRY) as u8;
+        self.status.set(StatusFlags::CARRY, value & 0x01 != 0);
+        let result = (value >> 1) | (carry << 7);
+        self.update_zero_and_negative_flags(result);
+        result
     }
 
-    pub fn set_decimal_flag(&mut self, decimal: bool) {
-        if decimal {
-            self.set_flag(0b0000_1000); // Set D flag
+    // A taken branch costs one extra cycle, and another if it lands on a
+    // different page from the instruction that follows it.
+    fn branch(&mut self, condition: bool) {
+        if condition {
+            let offset = self.memory.read(self.pc) as i8; // Read signed offset
+            self.pc = self.pc.wrapping_add(1); // Increment program counter
+            let target = self.pc.wrapping_add_signed(offset as i16); // Calculate new address
+            self.extra_cycles += 1;
+            if target & 0xFF00 != self.pc & 0xFF00 {
+                self.extra_cycles += 1;
+            }
+            self.pc = target;
         } else {
-            self.clear_flag(0b0000_1000); // Clear D flag
+            self.pc = self.pc.wrapping_add(1); // Skip the offset byte
         }
     }
 
-    pub fn set_interrupt_disable_flag(&mut self, interrupt_disable: bool) {
-        if interrupt_disable {
-            self.set_flag(0b0000_0100); // Set I flag
-        } else {
-            self.clear_flag(0b0000_0100); // Clear I flag
-        }
+    fn clear_carry(&mut self) {
+        self.status.remove(StatusFlags::CARRY);
     }
 
-    pub fn set_zero_flag(&mut self, value: u8) {
-        if value == 0 {
-            self.set_flag(0b0000_0010); // Set Z flag
-        } else {
-            self.clear_flag(0b0000_0010); // Clear Z flag
-        }
+    fn clear_decimal(&mut self) {
+        self.status.remove(StatusFlags::DECIMAL);
     }
 
-    pub fn set_carry_flag(&mut self, carry: bool) {
-        if carry {
-            self.set_flag(0b0000_0001); // Set C flag
-        } else {
-            self.clear_flag(0b0000_0001); // Clear C flag
-        }
+    fn clear_interrupt(&mut self) {
+        self.status.remove(StatusFlags::INTERRUPT_DISABLE);
     }
 
-    pub fn get_negative_flag(&self) -> bool {
-        self.status & 0b1000_0000 != 0
+    fn clear_overflow(&mut self) {
+        self.status.remove(StatusFlags::OVERFLOW);
     }
 
-    pub fn get_overflow_flag(&self) -> bool {
-        self.status & 0b0100_0000 != 0
+    fn set_carry(&mut self) {
+        self.status.insert(StatusFlags::CARRY);
     }
 
-    pub fn get_break_flag(&self) -> bool {
-        self.status & 0b0001_0000 != 0
+    fn set_decimal(&mut self) {
+        self.status.insert(StatusFlags::DECIMAL);
     }
 
-    pub fn get_decimal_flag(&self) -> bool {
-        self.status & 0b0000_1000 != 0
+    fn set_interrupt(&mut self) {
+        self.status.insert(StatusFlags::INTERRUPT_DISABLE);
     }
 
-    pub fn get_interrupt_disable_flag(&self) -> bool {
-        self.status & 0b0000_0100 != 0
+    fn load_a(&mut self, value: u8) {
+        self.a = value;
+        self.update_zero_and_negative_flags(self.a);
     }
 
-    pub fn get_zero_flag(&self) -> bool {
-        self.status & 0b0000_0010 != 0
+    fn load_x(&mut self, value: u8) {
+        self.x = value;
+        self.update_zero_and_negative_flags(self.x);
     }
 
-    pub fn get_carry_flag(&self) -> bool {
-        self.status & 0b0000_0001 != 0
+    fn load_y(&mut self, value: u8) {
+        self.y = value;
+        self.update_zero_and_negative_flags(self.y);
     }
 
     fn push(&mut self, value: u8) {
-        self.memory.write(0x0100 + self.sp as u16, value);
+        self.mem_write(0x0100 + self.sp as u16, value);
         self.sp = self.sp.wrapping_sub(1);
     }
 
@@ -454,24 +1205,21 @@ impl CPU {
         self.memory.read(0x0100 + self.sp as u16)
     }
 
-    fn sbc(&mut self, mode: &AddressingMode) {
-        let value = self.get_operand(mode);
-        let carry = self.get_carry_flag() as u8;
-        let result = self.a.wrapping_sub(value).wrapping_sub(carry);
-
-        self.set_carry_flag(self.a >= value);
-        self.set_overflow_flag((self.a ^ value) & (self.a ^ result) & 0x80 != 0);
+    fn push_u16(&mut self, value: u16) {
+        self.push((value >> 8) as u8);
+        self.push((value & 0x00FF) as u8);
+    }
 
-        self.a = result;
-        self.update_zero_and_negative_flags(self.a);
+    fn pull_u16(&mut self) -> u16 {
+        let low = self.pull() as u16;
+        let high = self.pull() as u16;
+        (high << 8) | low
     }
 
-    fn inc(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
+    fn inc(&mut self, value: u8) -> u8 {
         let result = value.wrapping_add(1);
-        self.mem_write(addr, result);
         self.update_zero_and_negative_flags(result);
+        result
     }
 
     fn inx(&mut self) {
@@ -484,12 +1232,10 @@ impl CPU {
         self.update_zero_and_negative_flags(self.y);
     }
 
-    fn dec(&mut self, mode: &AddressingMode) {
-        let addr = self.get_operand_address(mode);
-        let value = self.mem_read(addr);
+    fn dec(&mut self, value: u8) -> u8 {
         let result = value.wrapping_sub(1);
-        self.mem_write(addr, result);
         self.update_zero_and_negative_flags(result);
+        result
     }
 
     fn dex(&mut self) {
@@ -502,68 +1248,126 @@ impl CPU {
         self.update_zero_and_negative_flags(self.y);
     }
 
+    fn fetch(&mut self) -> u8 {
+        let value = self.memory.read(self.pc);
+        self.pc = self.pc.wrapping_add(1);
+        value
+    }
+
+    fn read_u16(&mut self, address: u16) -> u16 {
+        let low = self.memory.read(address) as u16;
+        let high = self.memory.read(address.wrapping_add(1)) as u16;
+        (high << 8) | low
+    }
+
+    fn fetch_u16(&mut self) -> u16 {
+        let value = self.read_u16(self.pc);
+        self.pc = self.pc.wrapping_add(2);
+        value
+    }
+
+    // Reads through an indexed address take an extra cycle when indexing
+    // crossed a page. Stores and read-modify-write instructions always spend
+    // that cycle, so it is already part of their base count.
     fn get_operand(&mut self, mode: &AddressingMode) -> u8 {
-        match mode {
-            AddressingMode::Immediate => {
-                let val = self.memory.read(self.pc);
-                self.pc += 1;
-                val
-            }
-            _ => {
-                let addr = self.get_operand_address(mode);
-                self.memory.read(addr)
-            }
+        let addr = self.get_operand_address(mode);
+        if self.page_crossed {
+            self.extra_cycles += 1;
         }
+        self.memory.read(addr)
     }
 
-    fn get_operand_address(&self, mode: &AddressingMode) -> u16 {
+    // Resolves the effective address for `mode`, consuming the operand bytes so
+    // that the PC ends up on the next opcode.
+    fn get_operand_address(&mut self, mode: &AddressingMode) -> u16 {
+        self.page_crossed = false;
         match mode {
-            AddressingMode::ZeroPage => self.memory.read(self.pc) as u16,
+            AddressingMode::Immediate => {
+                let addr = self.pc;
+                self.pc = self.pc.wrapping_add(1);
+                addr
+            }
+            AddressingMode::ZeroPage => self.fetch() as u16,
             AddressingMode::ZeroPageX => {
-                let pos = self.memory.read(self.pc);
+                let pos = self.fetch();
                 pos.wrapping_add(self.x) as u16
             }
             AddressingMode::ZeroPageY => {
-                let pos = self.memory.read(self.pc);
+                let pos = self.fetch();
                 pos.wrapping_add(self.y) as u16
             }
-            AddressingMode::Absolute => self.memory.read_u16(self.pc),
+            AddressingMode::Absolute => self.fetch_u16(),
             AddressingMode::AbsoluteX => {
-                let base = self.memory.read_u16(self.pc);
-                base.wrapping_add(self.x as u16)
+                let base = self.fetch_u16();
+                self.index(base, self.x)
             }
             AddressingMode::AbsoluteY => {
-                let base = self.memory.read_u16(self.pc);
-                base.wrapping_add(self.y as u16)
+                let base = self.fetch_u16();
+                self.index(base, self.y)
             }
             AddressingMode::IndirectX => {
-                let base = self.memory.read(self.pc);
+                let base = self.fetch();
                 let ptr = base.wrapping_add(self.x);
                 let lo = self.memory.read(ptr as u16);
                 let hi = self.memory.read(ptr.wrapping_add(1) as u16);
                 (hi as u16) << 8 | (lo as u16)
             }
             AddressingMode::IndirectY => {
-                let base = self.memory.read(self.pc);
+                let base = self.fetch();
                 let lo = self.memory.read(base as u16);
                 let hi = self.memory.read(base.wrapping_add(1) as u16);
                 let deref_base = (hi as u16) << 8 | (lo as u16);
-                deref_base.wrapping_add(self.y as u16)
+                self.index(deref_base, self.y)
+            }
+            AddressingMode::ZeroPageIndirect => {
+                let base = self.fetch();
+                let lo = self.memory.read(base as u16);
+                let hi = self.memory.read(base.wrapping_add(1) as u16);
+                (hi as u16) << 8 | (lo as u16)
+            }
+            AddressingMode::Indirect => {
+                let ptr = self.fetch_u16();
+                let lo = self.memory.read(ptr);
+                let hi = self.memory.read(self.indirect_high_address(ptr));
+                (hi as u16) << 8 | (lo as u16)
+            }
+            AddressingMode::AbsoluteIndexedIndirect => {
+                let ptr = self.fetch_u16().wrapping_add(self.x as u16);
+                let lo = self.memory.read(ptr);
+                let hi = self.memory.read(ptr.wrapping_add(1));
+                (hi as u16) << 8 | (lo as u16)
             }
-            _ => panic!("mode {:?} not supported", mode),
+            // Every mode in the opcode tables that has an operand is handled
+            _ => unreachable!("no operand address for {:?}", mode),
         }
     }
 
-    fn mem_read(&self, addr: u16) -> u8 {
+    // Where JMP (abs) finds the high byte of its target. The NMOS part does
+    // not carry into the pointer's high byte, so JMP ($10FF) reads $1000.
+    fn indirect_high_address(&self, ptr: u16) -> u16 {
+        if self.variant.is_cmos() {
+            ptr.wrapping_add(1)
+        } else {
+            (ptr & 0xFF00) | (ptr as u8).wrapping_add(1) as u16
+        }
+    }
+
+    fn index(&mut self, base: u16, offset: u8) -> u16 {
+        let addr = base.wrapping_add(offset as u16);
+        self.page_crossed = base & 0xFF00 != addr & 0xFF00;
+        addr
+    }
+
+    fn mem_read(&mut self, addr: u16) -> u8 {
         self.memory.read(addr)
     }
 
     fn mem_write(&mut self, addr: u16, value: u8) {
-        self.memory.write(addr, value)
+        self.memory.write(addr, value);
+        self.watch_write(addr, value);
     }
 
     fn update_zero_and_negative_flags(&mut self, value: u8) {
-        self.set_zero_flag(value);
-        self.set_negative_flag(value);
+        self.status.set_zero_and_negative(value);
     }
 }
@@ -0,0 +1,629 @@
+use super::{Bus, CPU, HaltReason, INTERRUPT_CYCLES, IRQ_VECTOR, Operation, StatusFlags};
+use crate::assembler::{AddressingMode, Instruction, OpCode};
+
+/// Direction of a bus transaction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BusAccess {
+    Read,
+    Write,
+}
+
+/// The bus transaction performed during a single clock cycle. The 6502 reads
+/// or writes on every cycle, including the dummy accesses whose data it
+/// throws away.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BusCycle {
+    pub address: u16,
+    pub value: u8,
+    pub access: BusAccess,
+}
+
+// Progress through an instruction or interrupt sequence being stepped one
+// cycle at a time
+#[derive(Debug, Clone, Copy)]
+pub(super) struct InFlight {
+    op: OpCode,
+    mode: AddressingMode,
+    cycles: u8,             // Base cycle count from the opcode table
+    interrupt: Option<u16>, // Vector of a hardware interrupt sequence
+    step: u8,               // Cycles run since the opcode fetch
+    data_step: u8,          // Cycles run since the effective address was known
+    address_ready: bool,
+    addr: u16, // Effective address, or a pointer while one is being followed
+    base: u16, // Address before indexing
+    value: u8, // Data latched from the bus
+    taken: bool,
+}
+
+impl InFlight {
+    pub(super) fn is_interrupt(&self) -> bool {
+        self.interrupt.is_some()
+    }
+
+    fn new(op: OpCode, mode: AddressingMode, cycles: u8, interrupt: Option<u16>) -> Self {
+        Self {
+            op,
+            mode,
+            cycles,
+            interrupt,
+            step: 0,
+            data_step: 0,
+            address_ready: false,
+            addr: 0,
+            base: 0,
+            value: 0,
+            taken: false,
+        }
+    }
+}
+
+impl<B: Bus> CPU<B> {
+    /// Advances the CPU by one clock cycle, performing the bus access the
+    /// hardware makes on that cycle. Returns `None` once the CPU is halted.
+    ///
+    /// Instructions take effect on the cycle the hardware completes them, so
+    /// `tick` and `execute_instruction` can be mixed freely at instruction
+    /// boundaries.
+    pub fn tick(&mut self) -> Option<BusCycle> {
+        if self.in_flight.is_none() {
+            self.check_stop_conditions();
+        }
+        if self.is_halted() {
+            return None;
+        }
+        self.spend_cycles(1);
+        let Some(mut state) = self.in_flight.take() else {
+            if self.waiting {
+                // WAI holds the bus on the next opcode until an interrupt
+                if !self.interrupt_requested() {
+                    return Some(self.bus_read(self.pc));
+                }
+                self.waiting = false;
+            }
+            return Some(self.begin_sequence());
+        };
+        let (bus, done) = self.run_cycle(&mut state);
+        state.step += 1;
+        if !done {
+            self.in_flight = Some(state);
+        }
+        Some(bus)
+    }
+
+    // First cycle of an instruction: the opcode fetch, or the discarded fetch
+    // that starts an interrupt sequence
+    fn begin_sequence(&mut self) -> BusCycle {
+        if self.variant.is_65816() {
+            return self.begin_coarse_sequence();
+        }
+        if let Some(vector) = self.pending_interrupt() {
+            self.in_flight = Some(InFlight::new(
+                OpCode::BRK,
+                AddressingMode::Implied,
+                INTERRUPT_CYCLES,
+                Some(vector),
+            ));
+            return self.bus_read(self.pc);
+        }
+
+        let bus = self.bus_fetch(self.pc);
+        self.pc = self.pc.wrapping_add(1);
+        let Some(Instruction {
+            opname,
+            mode,
+            cycles,
+            ..
+        }) = self.decode(bus.value)
+        else {
+            return bus;
+        };
+        match opname {
+            OpCode::BRK if self.halt_conditions.brk => self.halt(HaltReason::Brk),
+            OpCode::RTS
+                if self.halt_conditions.top_level_rts && self.stack_pointer() >= self.stack_top =>
+            {
+                self.halt(HaltReason::TopLevelRts)
+            }
+            // The 65C02's single-cycle NOPs are over once fetched
+            _ if *cycles == 1 => {}
+            _ => self.in_flight = Some(InFlight::new(*opname, *mode, *cycles, None)),
+        }
+        bus
+    }
+
+    // The 65C816 is not stepped bus cycle by bus cycle: its instructions and
+    // interrupts run whole on the first cycle, and the cycles that remain
+    // idle on the bus at PC
+    fn begin_coarse_sequence(&mut self) -> BusCycle {
+        let address = (self.regs816.pbr as u32) << 16 | self.pc as u32;
+        let bus = BusCycle {
+            address: self.pc,
+            value: self.memory.read_long(address),
+            access: BusAccess::Read,
+        };
+        let cycles = self.run_instruction().cycles();
+        if cycles > 1 && !self.is_halted() {
+            self.in_flight = Some(InFlight::new(
+                OpCode::NOP,
+                AddressingMode::Implied,
+                cycles,
+                None,
+            ));
+        }
+        bus
+    }
+
+    // Runs one cycle after the opcode fetch; returns the bus access and
+    // whether the sequence is complete
+    fn run_cycle(&mut self, s: &mut InFlight) -> (BusCycle, bool) {
+        match s.op {
+            OpCode::BRK => self.interrupt_cycle(s),
+            OpCode::JSR => self.jsr_cycle(s),
+            OpCode::RTS => self.rts_cycle(s),
+            OpCode::RTI => self.rti_cycle(s),
+            OpCode::PHA | OpCode::PHP | OpCode::PHX | OpCode::PHY => self.push_cycle(s),
+            OpCode::PLA | OpCode::PLP | OpCode::PLX | OpCode::PLY => self.pull_cycle(s),
+            OpCode::JMP => self.jmp_cycle(s),
+            op if self.branch_condition(op).is_some() => self.branch_cycle(s),
+            op if Self::bit_branch(op).is_some() => self.bit_branch_cycle(s),
+            op => match Self::operation(op, &s.mode) {
+                Operation::Other => self.implied_cycle(s),
+                Operation::Modify(apply) if s.mode == AddressingMode::Implied => {
+                    let bus = self.bus_read(self.pc);
+                    self.a = apply(self, self.a);
+                    (bus, true)
+                }
+                operation => self.memory_cycle(s, operation),
+            },
+        }
+    }
+
+    // Takes effect on the last cycle; WAI and STP spend an extra one
+    fn implied_cycle(&mut self, s: &mut InFlight) -> (BusCycle, bool) {
+        let bus = self.bus_read(self.pc);
+        let done = s.step + 2 >= s.cycles;
+        if done {
+            self.execute_other(s.op, &s.mode);
+        }
+        (bus, done)
+    }
+
+    fn memory_cycle(&mut self, s: &mut InFlight, operation: Operation<B>) -> (BusCycle, bool) {
+        if !s.address_ready {
+            // Writes and most read-modify-writes always spend the cycle that
+            // fixes the high byte of an indexed address; reads only when it
+            // changes.
+            let always_fix = match operation {
+                Operation::Read(_) => false,
+                Operation::Modify(_) => self.modify_always_fixes(s.op),
+                _ => true,
+            };
+            if let Some(bus) = self.address_cycle(s, always_fix) {
+                return (bus, false);
+            }
+            s.address_ready = true;
+        }
+
+        let step = s.data_step;
+        s.data_step += 1;
+        match (operation, step) {
+            // Any cycles beyond the data read, for a 65C02 decimal correction
+            // or one of its long NOPs, re-read the same address
+            (Operation::Read(apply), _) => {
+                let bus = self.bus_read(s.addr);
+                if step == 0 {
+                    s.value = bus.value;
+                }
+                let done = step >= self.decimal_cycle(s.op) as u8 && s.step + 2 >= s.cycles;
+                if done {
+                    apply(self, s.value);
+                }
+                (bus, done)
+            }
+            (Operation::Store(register), _) => {
+                let value = register(self);
+                (self.bus_write(s.addr, value), true)
+            }
+            (Operation::StoreHigh(register), _) => {
+                let value = register(self);
+                let (addr, value) = Self::high_byte_store(s.base, s.addr, value);
+                (self.bus_write(addr, value), true)
+            }
+            (Operation::Modify(_), 0) => {
+                let bus = self.bus_read(s.addr);
+                s.value = bus.value;
+                (bus, false)
+            }
+            // The NMOS part writes the unmodified value back while the ALU
+            // works; the 65C02 reads it again instead
+            (Operation::Modify(apply), 1) => {
+                let bus = if self.variant.is_cmos() {
+                    self.bus_read(s.addr)
+                } else {
+                    self.bus_write(s.addr, s.value)
+                };
+                s.value = apply(self, s.value);
+                (bus, false)
+            }
+            (Operation::Modify(_), _) => (self.bus_write(s.addr, s.value), true),
+            (Operation::Other, _) => unreachable!(),
+        }
+    }
+
+    // Performs the next cycle of effective address calculation, or returns
+    // None once `s.addr` holds the effective address
+    fn address_cycle(&mut self, s: &mut InFlight, always_fix: bool) -> Option<BusCycle> {
+        match (s.mode, s.step) {
+            (AddressingMode::Immediate, _) => {
+                s.addr = self.pc;
+                self.pc = self.pc.wrapping_add(1);
+                None
+            }
+            (AddressingMode::ZeroPage, 0) => Some(self.fetch_address_low(s)),
+            (AddressingMode::ZeroPageX | AddressingMode::ZeroPageY, 0) => {
+                Some(self.fetch_address_low(s))
+            }
+            (AddressingMode::ZeroPageX, 1) => {
+                let bus = self.bus_read(s.addr);
+                s.addr = (s.addr as u8).wrapping_add(self.x) as u16;
+                Some(bus)
+            }
+            (AddressingMode::ZeroPageY, 1) => {
+                let bus = self.bus_read(s.addr);
+                s.addr = (s.addr as u8).wrapping_add(self.y) as u16;
+                Some(bus)
+            }
+            (
+                AddressingMode::Absolute | AddressingMode::AbsoluteX | AddressingMode::AbsoluteY,
+                0,
+            ) => Some(self.fetch_address_low(s)),
+            (AddressingMode::Absolute, 1) => Some(self.fetch_address_high(s)),
+            (AddressingMode::AbsoluteX, 1) => {
+                let bus = self.fetch_address_high(s);
+                self.index_address(s, self.x);
+                Some(bus)
+            }
+            (AddressingMode::AbsoluteY, 1) => {
+                let bus = self.fetch_address_high(s);
+                self.index_address(s, self.y);
+                Some(bus)
+            }
+            (AddressingMode::AbsoluteX | AddressingMode::AbsoluteY, 2) => {
+                self.fix_high_byte(s, always_fix)
+            }
+            (AddressingMode::IndirectX, 0) => Some(self.fetch_address_low(s)),
+            (AddressingMode::IndirectX, 1) => {
+                let bus = self.bus_read(s.addr);
+                s.base = (s.addr as u8).wrapping_add(self.x) as u16;
+                Some(bus)
+            }
+            (AddressingMode::IndirectX, 2) => {
+                let bus = self.bus_read(s.base);
+                s.addr = bus.value as u16;
+                Some(bus)
+            }
+            (AddressingMode::IndirectX, 3) => {
+                let bus = self.bus_read((s.base as u8).wrapping_add(1) as u16);
+                s.addr |= (bus.value as u16) << 8;
+                Some(bus)
+            }
+            (AddressingMode::IndirectY, 0) => {
+                let bus = self.fetch_address_low(s);
+                s.base = s.addr;
+                Some(bus)
+            }
+            (AddressingMode::IndirectY, 1) => {
+                let bus = self.bus_read(s.base);
+                s.addr = bus.value as u16;
+                Some(bus)
+            }
+            (AddressingMode::IndirectY, 2) => {
+                let bus = self.bus_read((s.base as u8).wrapping_add(1) as u16);
+                s.addr |= (bus.value as u16) << 8;
+                self.index_address(s, self.y);
+                Some(bus)
+            }
+            (AddressingMode::IndirectY, 3) => self.fix_high_byte(s, always_fix),
+            (AddressingMode::ZeroPageIndirect, 0) => {
+                let bus = self.fetch_address_low(s);
+                s.base = s.addr;
+                Some(bus)
+            }
+            (AddressingMode::ZeroPageIndirect, 1) => {
+                let bus = self.bus_read(s.base);
+                s.addr = bus.value as u16;
+                Some(bus)
+            }
+            (AddressingMode::ZeroPageIndirect, 2) => {
+                let bus = self.bus_read((s.base as u8).wrapping_add(1) as u16);
+                s.addr |= (bus.value as u16) << 8;
+                Some(bus)
+            }
+            _ => None,
+        }
+    }
+
+    fn fetch_address_low(&mut self, s: &mut InFlight) -> BusCycle {
+        let bus = self.bus_read(self.pc);
+        self.pc = self.pc.wrapping_add(1);
+        s.addr = bus.value as u16;
+        bus
+    }
+
+    fn fetch_address_high(&mut self, s: &mut InFlight) -> BusCycle {
+        let bus = self.bus_read(self.pc);
+        self.pc = self.pc.wrapping_add(1);
+        s.addr |= (bus.value as u16) << 8;
+        bus
+    }
+
+    // Adds the index to the low byte only; the carry into the high byte is
+    // applied a cycle later by fix_high_byte
+    fn index_address(&mut self, s: &mut InFlight, index: u8) {
+        s.base = s.addr;
+        s.addr = s.addr.wrapping_add(index as u16);
+    }
+
+    // Reads from the address formed before the carry into the high byte. If
+    // that address was already right and the instruction only reads, the
+    // read is the real data access and this cycle is not spent here. The
+    // 65C02 avoids the stray access by re-reading the last operand byte.
+    fn fix_high_byte(&mut self, s: &mut InFlight, always_fix: bool) -> Option<BusCycle> {
+        let unfixed = (s.base & 0xFF00) | (s.addr & 0x00FF);
+        if unfixed == s.addr && !always_fix {
+            return None;
+        }
+        if self.variant.is_cmos() {
+            return Some(self.bus_read(self.pc.wrapping_sub(1)));
+        }
+        Some(self.bus_read(unfixed))
+    }
+
+    fn branch_cycle(&mut self, s: &mut InFlight) -> (BusCycle, bool) {
+        if s.step == 0 {
+            s.taken = self.branch_condition(s.op) == Some(true);
+        }
+        self.relative_cycle(s, s.step)
+    }
+
+    // BBR/BBS read the zero page byte, and read it again while testing the
+    // bit, before fetching the offset like any other branch
+    fn bit_branch_cycle(&mut self, s: &mut InFlight) -> (BusCycle, bool) {
+        match s.step {
+            0 => (self.fetch_address_low(s), false),
+            1 => {
+                let bus = self.bus_read(s.addr);
+                s.value = bus.value;
+                (bus, false)
+            }
+            2 => {
+                let (bit, set) = Self::bit_branch(s.op).unwrap();
+                s.taken = (s.value >> bit) & 1 == set as u8;
+                (self.bus_read(s.addr), false)
+            }
+            step => self.relative_cycle(s, step - 3),
+        }
+    }
+
+    // Fetches the offset of a branch whose outcome is in `s.taken`, then
+    // moves PC across the page in the cycles a taken branch adds
+    fn relative_cycle(&mut self, s: &mut InFlight, step: u8) -> (BusCycle, bool) {
+        match step {
+            0 => {
+                let bus = self.bus_read(self.pc);
+                self.pc = self.pc.wrapping_add(1);
+                s.addr = self.pc.wrapping_add_signed(bus.value as i8 as i16);
+                (bus, !s.taken)
+            }
+            1 => {
+                // Dummy fetch of the next opcode while PCL is updated
+                let bus = self.bus_read(self.pc);
+                let crossed = s.addr & 0xFF00 != self.pc & 0xFF00;
+                self.pc = (self.pc & 0xFF00) | (s.addr & 0x00FF);
+                if !crossed {
+                    self.pc = s.addr;
+                }
+                (bus, !crossed)
+            }
+            _ => {
+                let bus = self.bus_read(self.pc);
+                self.pc = s.addr;
+                (bus, true)
+            }
+        }
+    }
+
+    // The 65C02 spends a cycle re-reading the high operand byte before
+    // following the pointer, adding X to it first for JMP (abs,X)
+    fn jmp_cycle(&mut self, s: &mut InFlight) -> (BusCycle, bool) {
+        let pointer_step = s.cycles - 3;
+        match (s.mode, s.step) {
+            (_, 0) => (self.fetch_address_low(s), false),
+            (AddressingMode::Absolute, _) => {
+                let bus = self.fetch_address_high(s);
+                self.pc = s.addr;
+                (bus, true)
+            }
+            (_, 1) => (self.fetch_address_high(s), false),
+            (_, step) if step < pointer_step => {
+                let bus = self.bus_read(self.pc.wrapping_sub(1));
+                if s.mode == AddressingMode::AbsoluteIndexedIndirect {
+                    s.addr = s.addr.wrapping_add(self.x as u16);
+                }
+                (bus, false)
+            }
+            (_, step) if step == pointer_step => {
+                let bus = self.bus_read(s.addr);
+                s.value = bus.value;
+                (bus, false)
+            }
+            _ => {
+                let high = match s.mode {
+                    AddressingMode::Indirect => self.indirect_high_address(s.addr),
+                    _ => s.addr.wrapping_add(1),
+                };
+                let bus = self.bus_read(high);
+                self.pc = ((bus.value as u16) << 8) | s.value as u16;
+                (bus, true)
+            }
+        }
+    }
+
+    fn jsr_cycle(&mut self, s: &mut InFlight) -> (BusCycle, bool) {
+        match s.step {
+            0 => (self.fetch_address_low(s), false),
+            1 => (self.bus_read(0x0100 + self.sp as u16), false),
+            2 => (self.bus_push((self.pc >> 8) as u8), false),
+            3 => (self.bus_push(self.pc as u8), false),
+            _ => {
+                let bus = self.bus_read(self.pc);
+                self.pc = ((bus.value as u16) << 8) | (s.addr & 0x00FF);
+                (bus, true)
+            }
+        }
+    }
+
+    fn rts_cycle(&mut self, s: &mut InFlight) -> (BusCycle, bool) {
+        match s.step {
+            0 => (self.bus_read(self.pc), false),
+            1 => (self.bus_read(0x0100 + self.sp as u16), false),
+            2 => {
+                let bus = self.bus_pull();
+                self.pc = (self.pc & 0xFF00) | bus.value as u16;
+                (bus, false)
+            }
+            3 => {
+                let bus = self.bus_pull();
+                self.pc = (self.pc & 0x00FF) | ((bus.value as u16) << 8);
+                (bus, false)
+            }
+            _ => {
+                // JSR pushed the address of its last byte, so step past it
+                let bus = self.bus_read(self.pc);
+                self.pc = self.pc.wrapping_add(1);
+                (bus, true)
+            }
+        }
+    }
+
+    fn rti_cycle(&mut self, s: &mut InFlight) -> (BusCycle, bool) {
+        match s.step {
+            0 => (self.bus_read(self.pc), false),
+            1 => (self.bus_read(0x0100 + self.sp as u16), false),
+            2 => {
+                let bus = self.bus_pull();
+                self.status = StatusFlags::pulled(bus.value);
+                (bus, false)
+            }
+            3 => {
+                let bus = self.bus_pull();
+                self.pc = (self.pc & 0xFF00) | bus.value as u16;
+                (bus, false)
+            }
+            _ => {
+                let bus = self.bus_pull();
+                self.pc = (self.pc & 0x00FF) | ((bus.value as u16) << 8);
+                (bus, true)
+            }
+        }
+    }
+
+    fn push_cycle(&mut self, s: &mut InFlight) -> (BusCycle, bool) {
+        if s.step == 0 {
+            return (self.bus_read(self.pc), false);
+        }
+        let value = match s.op {
+            OpCode::PHP => self.status.pushed_by_instruction(),
+            OpCode::PHX => self.x,
+            OpCode::PHY => self.y,
+            _ => self.a,
+        };
+        (self.bus_push(value), true)
+    }
+
+    fn pull_cycle(&mut self, s: &mut InFlight) -> (BusCycle, bool) {
+        match s.step {
+            0 => (self.bus_read(self.pc), false),
+            1 => (self.bus_read(0x0100 + self.sp as u16), false),
+            _ => {
+                let bus = self.bus_pull();
+                match s.op {
+                    OpCode::PLP => self.status = StatusFlags::pulled(bus.value),
+                    OpCode::PLX => self.load_x(bus.value),
+                    OpCode::PLY => self.load_y(bus.value),
+                    _ => self.load_a(bus.value),
+                }
+                (bus, true)
+            }
+        }
+    }
+
+    // BRK and the hardware interrupts share one sequence. BRK skips its
+    // padding byte where an interrupt re-reads PC without advancing it.
+    fn interrupt_cycle(&mut self, s: &mut InFlight) -> (BusCycle, bool) {
+        let vector = s.interrupt.unwrap_or(IRQ_VECTOR);
+        match s.step {
+            0 => {
+                let bus = self.bus_read(self.pc);
+                if s.interrupt.is_none() {
+                    self.pc = self.pc.wrapping_add(1);
+                }
+                (bus, false)
+            }
+            1 => (self.bus_push((self.pc >> 8) as u8), false),
+            2 => (self.bus_push(self.pc as u8), false),
+            3 => {
+                let bus = self.bus_push(self.status_for_push(s.interrupt.is_none()));
+                self.enter_interrupt();
+                (bus, false)
+            }
+            4 => {
+                let bus = self.bus_read(vector);
+                s.value = bus.value;
+                (bus, false)
+            }
+            _ => {
+                let bus = self.bus_read(vector.wrapping_add(1));
+                self.pc = ((bus.value as u16) << 8) | s.value as u16;
+                (bus, true)
+            }
+        }
+    }
+
+    // The opcode read that starts an instruction
+    fn bus_fetch(&mut self, address: u16) -> BusCycle {
+        BusCycle {
+            address,
+            value: self.memory.fetch(address),
+            access: BusAccess::Read,
+        }
+    }
+
+    fn bus_read(&mut self, address: u16) -> BusCycle {
+        BusCycle {
+            address,
+            value: self.memory.read(address),
+            access: BusAccess::Read,
+        }
+    }
+
+    fn bus_write(&mut self, address: u16, value: u8) -> BusCycle {
+        self.mem_write(address, value);
+        BusCycle {
+            address,
+            value,
+            access: BusAccess::Write,
+        }
+    }
+
+    fn bus_push(&mut self, value: u8) -> BusCycle {
+        let bus = self.bus_write(0x0100 + self.sp as u16, value);
+        self.sp = self.sp.wrapping_sub(1);
+        bus
+    }
+
+    fn bus_pull(&mut self) -> BusCycle {
+        self.sp = self.sp.wrapping_add(1);
+        self.bus_read(0x0100 + self.sp as u16)
+    }
+}
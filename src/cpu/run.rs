@@ -0,0 +1,98 @@
+use super::{Bus, CPU, CpuError, StepOutcome};
+
+/// Why a `run_*` call returned.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StopReason {
+    /// The number of instructions asked for has run.
+    InstructionLimit,
+    /// At least the number of cycles asked for has run. The instruction that
+    /// crossed the limit is finished, so a few more cycles may have run.
+    CycleLimit,
+    /// The condition given to `run_until` or `run_until_pc` was met.
+    Condition,
+    /// The CPU halted or could not go on.
+    Halted(CpuError),
+    /// The CPU is waiting after WAI with no interrupt requested. Only
+    /// `run_for_cycles` idles through the wait, as nothing else bounds it.
+    Waiting,
+}
+
+/// What a `run_*` call executed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RunSummary {
+    /// Instructions completed, including one that halts the CPU as it
+    /// finishes, not counting interrupt sequences.
+    pub instructions: u64,
+    /// Clock cycles spent, including interrupt sequences and WAI.
+    pub cycles: u64,
+    pub stop: StopReason,
+}
+
+impl<B: Bus> CPU<B> {
+    /// Runs until at least `cycles` clock cycles have passed.
+    pub fn run_for_cycles(&mut self, cycles: u64) -> RunSummary {
+        self.run(true, |_, summary| {
+            (summary.cycles >= cycles).then_some(StopReason::CycleLimit)
+        })
+    }
+
+    /// Runs `instructions` instructions.
+    pub fn run_for_instructions(&mut self, instructions: u64) -> RunSummary {
+        self.run(false, |_, summary| {
+            (summary.instructions >= instructions).then_some(StopReason::InstructionLimit)
+        })
+    }
+
+    /// Runs until `condition` holds, checking it before every instruction.
+    pub fn run_until(&mut self, mut condition: impl FnMut(&Self) -> bool) -> RunSummary {
+        self.run(false, |cpu, _| {
+            condition(cpu).then_some(StopReason::Condition)
+        })
+    }
+
+    /// Runs until PC reaches `address`, before the instruction there runs.
+    pub fn run_until_pc(&mut self, address: u16) -> RunSummary {
+        self.run_until(|cpu| cpu.pc == address)
+    }
+
+    // Executes instructions until `stop` gives a reason, or the CPU halts.
+    // Unless `idle`, a CPU left waiting after WAI also ends the run.
+    fn run(
+        &mut self,
+        idle: bool,
+        mut stop: impl FnMut(&Self, &RunSummary) -> Option<StopReason>,
+    ) -> RunSummary {
+        let start = self.cycles;
+        let mut summary = RunSummary {
+            instructions: 0,
+            cycles: 0,
+            stop: StopReason::Condition,
+        };
+        loop {
+            summary.cycles = self.cycles - start;
+            if let Some(reason) = stop(self, &summary) {
+                summary.stop = reason;
+                return summary;
+            }
+            if !idle && self.waiting && !self.interrupt_requested() {
+                summary.stop = StopReason::Waiting;
+                return summary;
+            }
+            let before = self.cycles;
+            match self.execute_instruction() {
+                Ok(StepOutcome::Executed(_)) => summary.instructions += 1,
+                Ok(_) => {}
+                Err(error) => {
+                    // Only a halt raised by running an instruction, such as
+                    // BRK or a write to the exit address, spends cycles
+                    if self.cycles > before {
+                        summary.instructions += 1;
+                    }
+                    summary.cycles = self.cycles - start;
+                    summary.stop = StopReason::Halted(error);
+                    return summary;
+                }
+            }
+        }
+    }
+}
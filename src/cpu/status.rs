@@ -0,0 +1,113 @@
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+
+/// The processor status register, P.
+///
+/// Bits 4 and 5 are not flags on the 6502. B only exists on a copy pushed
+/// to the stack, where it tells BRK and PHP apart from a hardware interrupt,
+/// and bit 5 reads as set. In native mode the 65C816 uses the same two bits
+/// as its X and M register width flags.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
+pub struct StatusFlags(u8);
+
+impl StatusFlags {
+    pub const CARRY: Self = Self(0b0000_0001);
+    pub const ZERO: Self = Self(0b0000_0010);
+    pub const INTERRUPT_DISABLE: Self = Self(0b0000_0100);
+    pub const DECIMAL: Self = Self(0b0000_1000);
+    pub const BREAK: Self = Self(0b0001_0000);
+    pub const UNUSED: Self = Self(0b0010_0000);
+    pub const OVERFLOW: Self = Self(0b0100_0000);
+    pub const NEGATIVE: Self = Self(0b1000_0000);
+
+    /// 65C816 native mode: set for 8-bit index registers.
+    pub const INDEX_8BIT: Self = Self::BREAK;
+    /// 65C816 native mode: set for an 8-bit accumulator.
+    pub const ACCUMULATOR_8BIT: Self = Self::UNUSED;
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Whether every flag in `flags` is set.
+    pub const fn contains(self, flags: Self) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+
+    pub fn insert(&mut self, flags: Self) {
+        self.0 |= flags.0;
+    }
+
+    pub fn remove(&mut self, flags: Self) {
+        self.0 &= !flags.0;
+    }
+
+    /// Sets or clears every flag in `flags`.
+    pub fn set(&mut self, flags: Self, value: bool) {
+        if value {
+            self.insert(flags);
+        } else {
+            self.remove(flags);
+        }
+    }
+
+    /// Sets Z and N from a result, as loads and most ALU operations do.
+    pub fn set_zero_and_negative(&mut self, value: u8) {
+        self.set(Self::ZERO, value == 0);
+        self.set(Self::NEGATIVE, value & 0x80 != 0);
+    }
+
+    /// The byte PHP and BRK push, with B and bit 5 set.
+    pub const fn pushed_by_instruction(self) -> u8 {
+        self.0 | Self::BREAK.0 | Self::UNUSED.0
+    }
+
+    /// The byte an IRQ or NMI pushes, with bit 5 set and B clear.
+    pub const fn pushed_by_interrupt(self) -> u8 {
+        (self.0 & !Self::BREAK.0) | Self::UNUSED.0
+    }
+
+    /// The register after PLP or RTI pulls `value`. B is dropped, since the
+    /// register has no such bit, and bit 5 reads as set.
+    pub const fn pulled(value: u8) -> Self {
+        Self((value & !Self::BREAK.0) | Self::UNUSED.0)
+    }
+}
+
+impl BitOr for StatusFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for StatusFlags {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+/// Formats the flags as `NV-BDIZC`, in upper case when set and lower case
+/// when clear. Bit 5 is always shown as `-`.
+impl fmt::Display for StatusFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (bit, letter) in (0..8).rev().zip("NV-BDIZC".chars()) {
+            let letter = if self.0 & (1 << bit) != 0 {
+                letter
+            } else {
+                letter.to_ascii_lowercase()
+            };
+            write!(f, "{}", letter)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,969 @@
+use super::{
+    Bus, CPU, HaltReason, INTERRUPT_CYCLES, IRQ_VECTOR, NMI_VECTOR, Operation, StatusFlags,
+};
+use crate::assembler::{AddressingMode, Instruction, OpCode};
+
+// Vectors used only in native mode, except COP which has one in each mode
+const COP_VECTOR_EMULATION: u16 = 0xFFF4;
+const COP_VECTOR: u16 = 0xFFE4;
+const BRK_VECTOR: u16 = 0xFFE6;
+const NMI_VECTOR_NATIVE: u16 = 0xFFEA;
+const IRQ_VECTOR_NATIVE: u16 = 0xFFEE;
+
+/// 65C816 registers beyond the 6502 set. The low bytes of the accumulator,
+/// index registers and stack pointer stay in `CPU::a`, `x`, `y` and `sp`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Registers816 {
+    pub b: u8,           // High byte of the accumulator, swapped in by XBA
+    pub xh: u8,          // High byte of X, zero while the index registers are 8 bits
+    pub yh: u8,          // High byte of Y, zero while the index registers are 8 bits
+    pub sh: u8,          // High byte of the stack pointer, $01 in emulation mode
+    pub d: u16,          // Direct page register
+    pub dbr: u8,         // Data bank register
+    pub pbr: u8,         // Program bank register
+    pub emulation: bool, // E flag, exchanged with carry by XCE
+}
+
+impl Default for Registers816 {
+    fn default() -> Self {
+        Self {
+            b: 0,
+            xh: 0,
+            yh: 0,
+            sh: 0x01,
+            d: 0,
+            dbr: 0,
+            pbr: 0,
+            emulation: true,
+        }
+    }
+}
+
+// A resolved data address. Direct page and stack accesses stay in bank 0, so
+// the second byte of a 16-bit access wraps within it.
+#[derive(Clone, Copy)]
+struct Target {
+    address: u32,
+    wrap: u32,
+}
+
+impl Target {
+    fn long(address: u32) -> Self {
+        Self {
+            address: address & 0xFF_FFFF,
+            wrap: 0xFF_FFFF,
+        }
+    }
+
+    fn bank0(address: u16) -> Self {
+        Self {
+            address: address as u32,
+            wrap: 0xFFFF,
+        }
+    }
+
+    fn next(self) -> u32 {
+        (self.address & !self.wrap) | (self.address.wrapping_add(1) & self.wrap)
+    }
+}
+
+// How a 16-bit accumulator or index instruction uses its operand
+enum WideOperation<B> {
+    Read(fn(&mut CPU<B>, u16)),
+    Modify(fn(&mut CPU<B>, u16) -> u16),
+    Store(fn(&CPU<B>) -> u16),
+}
+
+impl<B: Bus> CPU<B> {
+    /// The full 16-bit accumulator, B:A.
+    pub fn accumulator(&self) -> u16 {
+        (self.regs816.b as u16) << 8 | self.a as u16
+    }
+
+    /// The full 16-bit X register.
+    pub fn index_x(&self) -> u16 {
+        (self.regs816.xh as u16) << 8 | self.x as u16
+    }
+
+    /// The full 16-bit Y register.
+    pub fn index_y(&self) -> u16 {
+        (self.regs816.yh as u16) << 8 | self.y as u16
+    }
+
+    /// The full 16-bit stack pointer. Outside native mode the high byte is
+    /// always $01.
+    pub fn stack_pointer(&self) -> u16 {
+        (self.regs816.sh as u16) << 8 | self.sp as u16
+    }
+
+    fn set_accumulator(&mut self, value: u16) {
+        self.a = value as u8;
+        self.regs816.b = (value >> 8) as u8;
+    }
+
+    fn set_index_x(&mut self, value: u16) {
+        self.x = value as u8;
+        self.regs816.xh = (value >> 8) as u8;
+    }
+
+    fn set_index_y(&mut self, value: u16) {
+        self.y = value as u8;
+        self.regs816.yh = (value >> 8) as u8;
+    }
+
+    fn set_stack_pointer(&mut self, value: u16) {
+        self.sp = value as u8;
+        if !self.regs816.emulation {
+            self.regs816.sh = (value >> 8) as u8;
+        }
+    }
+
+    fn accumulator_is_wide(&self) -> bool {
+        !self.regs816.emulation && !self.status.contains(StatusFlags::ACCUMULATOR_8BIT)
+    }
+
+    fn index_is_wide(&self) -> bool {
+        !self.regs816.emulation && !self.status.contains(StatusFlags::INDEX_8BIT)
+    }
+
+    // Puts the processor in the state the 65C816 starts in after RESET. In
+    // emulation mode the register has no X or B bit, so bit 4 reads as clear
+    // just as after XCE, PLP or RTI; M shares bit 5, which is always set.
+    pub(super) fn reset_65816(&mut self) {
+        self.regs816 = Registers816 {
+            b: self.regs816.b,
+            ..Registers816::default()
+        };
+        self.status = StatusFlags::pulled(self.status.bits());
+    }
+
+    // Cycles taken by an interrupt sequence, which also pushes PBR in native mode
+    pub(super) fn interrupt_cycles(&self) -> u8 {
+        if self.variant.is_65816() && !self.regs816.emulation {
+            INTERRUPT_CYCLES + 1
+        } else {
+            INTERRUPT_CYCLES
+        }
+    }
+
+    // Interrupt entry for the 65C816. `vector` is the emulation mode vector;
+    // native mode has its own set, with BRK apart from IRQ, and pushes PBR.
+    pub(super) fn interrupt_65816(&mut self, vector: u16, brk: bool) {
+        if self.regs816.emulation {
+            self.push_u16_816(self.pc);
+            self.push_816(self.status_for_push(brk));
+        } else {
+            self.push_816(self.regs816.pbr);
+            self.push_u16_816(self.pc);
+            self.push_816(self.status.bits());
+        }
+        self.enter_interrupt();
+        let vector = match (self.regs816.emulation, vector) {
+            (true, _) => vector,
+            (false, NMI_VECTOR) => NMI_VECTOR_NATIVE,
+            (false, COP_VECTOR_EMULATION) => COP_VECTOR,
+            (false, _) if brk => BRK_VECTOR,
+            (false, _) => IRQ_VECTOR_NATIVE,
+        };
+        self.regs816.pbr = 0;
+        self.pc = self.read_u16_816(Target::bank0(vector));
+    }
+
+    // Executes the instruction at PBR:PC and returns its base cycle count,
+    // accruing penalties in `extra_cycles` like `step`
+    pub(super) fn step_65816(&mut self) -> u8 {
+        let opcode = self
+            .memory
+            .fetch_long((self.regs816.pbr as u32) << 16 | self.pc as u32);
+        self.pc = self.pc.wrapping_add(1);
+        let Some(&Instruction {
+            opname: op,
+            mode,
+            cycles,
+            ..
+        }) = self.decode(opcode)
+        else {
+            return 0;
+        };
+
+        match Self::operation(op, &mode) {
+            Operation::Other => self.execute_other_65816(op, &mode),
+            _ if self.is_wide(op) => self.execute_wide(op, &mode),
+            operation => self.execute_narrow(operation, &mode),
+        }
+        cycles
+    }
+
+    // Index instructions follow the X flag; everything else on a value
+    // follows M
+    fn is_wide(&self, op: OpCode) -> bool {
+        match op {
+            OpCode::LDX | OpCode::LDY | OpCode::STX | OpCode::STY | OpCode::CPX | OpCode::CPY => {
+                self.index_is_wide()
+            }
+            _ => self.accumulator_is_wide(),
+        }
+    }
+
+    // 8-bit operations run the same code as the 6502
+    fn execute_narrow(&mut self, operation: Operation<B>, mode: &AddressingMode) {
+        match operation {
+            Operation::Read(apply) => {
+                let value = self.read_operand_816(mode, false);
+                apply(self, value as u8);
+            }
+            Operation::Modify(apply) if *mode == AddressingMode::Implied => {
+                self.a = apply(self, self.a);
+            }
+            Operation::Modify(apply) => {
+                let target = self.effective_address(mode, false);
+                let value = self.memory.read_long(target.address);
+                let result = apply(self, value);
+                self.write_816(target.address, result);
+            }
+            Operation::Store(register) => {
+                let target = self.effective_address(mode, false);
+                let value = register(self);
+                self.write_816(target.address, value);
+            }
+            Operation::StoreHigh(_) | Operation::Other => unreachable!(),
+        }
+    }
+
+    // 16-bit operations spend a cycle on each extra data byte moved
+    fn execute_wide(&mut self, op: OpCode, mode: &AddressingMode) {
+        match Self::wide_operation(op, mode) {
+            WideOperation::Read(apply) => {
+                let value = self.read_operand_816(mode, true);
+                apply(self, value);
+            }
+            WideOperation::Modify(apply) if *mode == AddressingMode::Implied => {
+                let result = apply(self, self.accumulator());
+                self.set_accumulator(result);
+            }
+            WideOperation::Modify(apply) => {
+                let target = self.effective_address(mode, false);
+                let value = self.read_u16_816(target);
+                let result = apply(self, value);
+                self.write_u16_816(target, result);
+                self.extra_cycles += 2;
+            }
+            WideOperation::Store(register) => {
+                let target = self.effective_address(mode, false);
+                let value = register(self);
+                self.write_u16_816(target, value);
+                self.extra_cycles += 1;
+            }
+        }
+    }
+
+    fn wide_operation(op: OpCode, mode: &AddressingMode) -> WideOperation<B> {
+        match op {
+            OpCode::LDA => WideOperation::Read(Self::load_accumulator),
+            OpCode::LDX => WideOperation::Read(Self::load_index_x),
+            OpCode::LDY => WideOperation::Read(Self::load_index_y),
+            OpCode::ADC => WideOperation::Read(Self::adc16),
+            OpCode::SBC => WideOperation::Read(Self::sbc16),
+            OpCode::AND => {
+                WideOperation::Read(|cpu, value| cpu.load_accumulator(cpu.accumulator() & value))
+            }
+            OpCode::ORA => {
+                WideOperation::Read(|cpu, value| cpu.load_accumulator(cpu.accumulator() | value))
+            }
+            OpCode::EOR => {
+                WideOperation::Read(|cpu, value| cpu.load_accumulator(cpu.accumulator() ^ value))
+            }
+            OpCode::CMP => {
+                WideOperation::Read(|cpu, value| cpu.compare16(cpu.accumulator(), value))
+            }
+            OpCode::CPX => WideOperation::Read(|cpu, value| cpu.compare16(cpu.index_x(), value)),
+            OpCode::CPY => WideOperation::Read(|cpu, value| cpu.compare16(cpu.index_y(), value)),
+            OpCode::BIT if *mode == AddressingMode::Immediate => {
+                WideOperation::Read(|cpu, value| cpu.set_zero16(cpu.accumulator() & value))
+            }
+            OpCode::BIT => WideOperation::Read(Self::bit16),
+            OpCode::ASL => WideOperation::Modify(Self::asl16),
+            OpCode::LSR => WideOperation::Modify(Self::lsr16),
+            OpCode::ROL => WideOperation::Modify(Self::rol16),
+            OpCode::ROR => WideOperation::Modify(Self::ror16),
+            OpCode::INC => WideOperation::Modify(|cpu, value| {
+                let result = value.wrapping_add(1);
+                cpu.update_zero_and_negative16(result);
+                result
+            }),
+            OpCode::DEC => WideOperation::Modify(|cpu, value| {
+                let result = value.wrapping_sub(1);
+                cpu.update_zero_and_negative16(result);
+                result
+            }),
+            OpCode::TSB => WideOperation::Modify(|cpu, value| {
+                cpu.set_zero16(cpu.accumulator() & value);
+                value | cpu.accumulator()
+            }),
+            OpCode::TRB => WideOperation::Modify(|cpu, value| {
+                cpu.set_zero16(cpu.accumulator() & value);
+                value & !cpu.accumulator()
+            }),
+            OpCode::STA => WideOperation::Store(Self::accumulator),
+            OpCode::STX => WideOperation::Store(Self::index_x),
+            OpCode::STY => WideOperation::Store(Self::index_y),
+            OpCode::STZ => WideOperation::Store(|_| 0),
+            _ => unreachable!("{:?} has no 16-bit form", op),
+        }
+    }
+
+    // Implied, stack, control-flow and mode instructions. Those that work
+    // the same way as on the 6502 are passed on to `execute_other`.
+    fn execute_other_65816(&mut self, op: OpCode, mode: &AddressingMode) {
+        if let Some(taken) = self.branch_condition(op) {
+            let offset = self.fetch_816() as i8 as u16;
+            self.branch_to(taken, self.pc.wrapping_add(offset));
+            return;
+        }
+        match op {
+            OpCode::BRL => {
+                let offset = self.fetch_u16_816();
+                self.pc = self.pc.wrapping_add(offset);
+            }
+            OpCode::INX => self.load_index_x(self.index_x().wrapping_add(1)),
+            OpCode::INY => self.load_index_y(self.index_y().wrapping_add(1)),
+            OpCode::DEX => self.load_index_x(self.index_x().wrapping_sub(1)),
+            OpCode::DEY => self.load_index_y(self.index_y().wrapping_sub(1)),
+            OpCode::TAX => self.load_index_x(self.accumulator()),
+            OpCode::TAY => self.load_index_y(self.accumulator()),
+            OpCode::TXA => self.load_accumulator(self.index_x()),
+            OpCode::TYA => self.load_accumulator(self.index_y()),
+            OpCode::TXY => self.load_index_y(self.index_x()),
+            OpCode::TYX => self.load_index_x(self.index_y()),
+            OpCode::TSX => self.load_index_x(self.stack_pointer()),
+            OpCode::TXS => {
+                self.set_stack_pointer(self.index_x());
+                self.stack_top = self.stack_pointer();
+            }
+            OpCode::TCS => {
+                self.set_stack_pointer(self.accumulator());
+                self.stack_top = self.stack_pointer();
+            }
+            OpCode::TSC => {
+                self.set_accumulator(self.stack_pointer());
+                self.update_zero_and_negative16(self.accumulator());
+            }
+            OpCode::TCD => {
+                self.regs816.d = self.accumulator();
+                self.update_zero_and_negative16(self.regs816.d);
+            }
+            OpCode::TDC => {
+                self.set_accumulator(self.regs816.d);
+                self.update_zero_and_negative16(self.regs816.d);
+            }
+            OpCode::XBA => {
+                std::mem::swap(&mut self.a, &mut self.regs816.b);
+                self.update_zero_and_negative_flags(self.a);
+            }
+            OpCode::XCE => self.exchange_carry_and_emulation(),
+            OpCode::REP => {
+                let mask = self.fetch_816();
+                self.set_status_816(self.status.bits() & !mask);
+            }
+            OpCode::SEP => {
+                let mask = self.fetch_816();
+                self.set_status_816(self.status.bits() | mask);
+            }
+            OpCode::PHA => {
+                if self.accumulator_is_wide() {
+                    self.push_u16_816(self.accumulator());
+                    self.extra_cycles += 1;
+                } else {
+                    self.push_816(self.a);
+                }
+            }
+            OpCode::PHX | OpCode::PHY => {
+                let value = match op {
+                    OpCode::PHX => self.index_x(),
+                    _ => self.index_y(),
+                };
+                if self.index_is_wide() {
+                    self.push_u16_816(value);
+                    self.extra_cycles += 1;
+                } else {
+                    self.push_816(value as u8);
+                }
+            }
+            OpCode::PLA => {
+                let value = if self.accumulator_is_wide() {
+                    self.extra_cycles += 1;
+                    self.pull_u16_816()
+                } else {
+                    self.pull_816() as u16
+                };
+                self.load_accumulator(value);
+            }
+            OpCode::PLX | OpCode::PLY => {
+                let value = if self.index_is_wide() {
+                    self.extra_cycles += 1;
+                    self.pull_u16_816()
+                } else {
+                    self.pull_816() as u16
+                };
+                match op {
+                    OpCode::PLX => self.load_index_x(value),
+                    _ => self.load_index_y(value),
+                }
+            }
+            OpCode::PHP => {
+                let status = if self.regs816.emulation {
+                    self.status.pushed_by_instruction()
+                } else {
+                    self.status.bits()
+                };
+                self.push_816(status);
+            }
+            OpCode::PLP => {
+                let status = self.pull_816();
+                self.set_status_816(status);
+            }
+            OpCode::PHB => self.push_816(self.regs816.dbr),
+            OpCode::PHK => self.push_816(self.regs816.pbr),
+            OpCode::PHD => self.push_u16_816(self.regs816.d),
+            OpCode::PLB => {
+                self.regs816.dbr = self.pull_816();
+                self.update_zero_and_negative_flags(self.regs816.dbr);
+            }
+            OpCode::PLD => {
+                self.regs816.d = self.pull_u16_816();
+                self.update_zero_and_negative16(self.regs816.d);
+            }
+            OpCode::PEA => {
+                let value = self.fetch_u16_816();
+                self.push_u16_816(value);
+            }
+            OpCode::PEI => {
+                let offset = self.fetch_816() as u16;
+                let value = self.read_direct_u16(offset);
+                self.push_u16_816(value);
+            }
+            OpCode::PER => {
+                let offset = self.fetch_u16_816();
+                self.push_u16_816(self.pc.wrapping_add(offset));
+            }
+            OpCode::JMP => self.jmp_816(mode),
+            OpCode::JML => {
+                let target = match mode {
+                    AddressingMode::AbsoluteIndirectLong => {
+                        let pointer = self.fetch_u16_816();
+                        self.read_u24_816(pointer)
+                    }
+                    _ => self.fetch_u24_816(),
+                };
+                self.regs816.pbr = (target >> 16) as u8;
+                self.pc = target as u16;
+            }
+            OpCode::JSR => {
+                let pointer = self.fetch_u16_816();
+                // The return address pushed is the last byte of the JSR instruction
+                self.push_u16_816(self.pc.wrapping_sub(1));
+                self.pc = match mode {
+                    AddressingMode::AbsoluteIndexedIndirect => {
+                        let pointer = pointer.wrapping_add(self.index_x());
+                        self.read_u16_816(self.program_target(pointer))
+                    }
+                    _ => pointer,
+                };
+            }
+            OpCode::JSL => {
+                let target = self.fetch_u24_816();
+                self.push_816(self.regs816.pbr);
+                self.push_u16_816(self.pc.wrapping_sub(1));
+                self.regs816.pbr = (target >> 16) as u8;
+                self.pc = target as u16;
+            }
+            OpCode::RTS => {
+                if self.halt_conditions.top_level_rts && self.stack_pointer() >= self.stack_top {
+                    self.halt(HaltReason::TopLevelRts);
+                    return;
+                }
+                self.pc = self.pull_u16_816().wrapping_add(1);
+            }
+            OpCode::RTL => {
+                self.pc = self.pull_u16_816().wrapping_add(1);
+                self.regs816.pbr = self.pull_816();
+            }
+            OpCode::RTI => {
+                let status = self.pull_816();
+                self.pc = self.pull_u16_816();
+                if self.regs816.emulation {
+                    self.status = StatusFlags::pulled(status);
+                } else {
+                    self.set_status_816(status);
+                    self.regs816.pbr = self.pull_816();
+                    self.extra_cycles += 1;
+                }
+            }
+            OpCode::BRK | OpCode::COP => {
+                if op == OpCode::BRK && self.halt_conditions.brk {
+                    self.halt(HaltReason::Brk);
+                    return;
+                }
+                // The byte after the opcode is a signature for the handler
+                self.pc = self.pc.wrapping_add(1);
+                if !self.regs816.emulation {
+                    self.extra_cycles += 1;
+                }
+                match op {
+                    OpCode::BRK => self.interrupt_65816(IRQ_VECTOR, true),
+                    _ => self.interrupt_65816(COP_VECTOR_EMULATION, false),
+                }
+            }
+            OpCode::MVN | OpCode::MVP => self.block_move(op),
+            OpCode::WDM => self.pc = self.pc.wrapping_add(1),
+            _ => self.execute_other(op, mode),
+        }
+    }
+
+    fn branch_to(&mut self, taken: bool, target: u16) {
+        if !taken {
+            return;
+        }
+        self.extra_cycles += 1;
+        // Only emulation mode spends a cycle when the branch crosses a page
+        if self.regs816.emulation && target & 0xFF00 != self.pc & 0xFF00 {
+            self.extra_cycles += 1;
+        }
+        self.pc = target;
+    }
+
+    // JMP stays within the program bank; JMP (abs) reads its pointer from
+    // bank 0 and JMP (abs,X) from the program bank
+    fn jmp_816(&mut self, mode: &AddressingMode) {
+        let operand = self.fetch_u16_816();
+        self.pc = match mode {
+            AddressingMode::Indirect => self.read_u16_816(Target::bank0(operand)),
+            AddressingMode::AbsoluteIndexedIndirect => {
+                let pointer = operand.wrapping_add(self.index_x());
+                self.read_u16_816(self.program_target(pointer))
+            }
+            _ => operand,
+        };
+    }
+
+    // Moves one byte from the source bank at X to the destination bank at Y
+    // and counts C down. The instruction repeats by leaving PC on itself
+    // until C wraps to $FFFF, so interrupts are taken between bytes.
+    fn block_move(&mut self, op: OpCode) {
+        let destination = self.fetch_816();
+        let source = self.fetch_816();
+        let value = self
+            .memory
+            .read_long((source as u32) << 16 | self.index_x() as u32);
+        self.write_816((destination as u32) << 16 | self.index_y() as u32, value);
+        self.regs816.dbr = destination;
+
+        let step = match op {
+            OpCode::MVN => 1,
+            _ => 0xFFFF,
+        };
+        let (x, y) = (
+            self.index_x().wrapping_add(step),
+            self.index_y().wrapping_add(step),
+        );
+        if self.index_is_wide() {
+            self.set_index_x(x);
+            self.set_index_y(y);
+        } else {
+            self.x = x as u8;
+            self.y = y as u8;
+        }
+
+        let count = self.accumulator().wrapping_sub(1);
+        self.set_accumulator(count);
+        if count != 0xFFFF {
+            self.pc = self.pc.wrapping_sub(3);
+        }
+    }
+
+    // XCE swaps carry with E. Entering native mode leaves both registers 8
+    // bits wide; entering emulation forces them back, with the stack on page 1.
+    fn exchange_carry_and_emulation(&mut self) {
+        let emulation = self.status.contains(StatusFlags::CARRY);
+        self.status.set(StatusFlags::CARRY, self.regs816.emulation);
+        self.regs816.emulation = emulation;
+        if emulation {
+            self.regs816.xh = 0;
+            self.regs816.yh = 0;
+            self.regs816.sh = 0x01;
+            self.status = StatusFlags::pulled(self.status.bits());
+        } else {
+            self.status |= StatusFlags::ACCUMULATOR_8BIT | StatusFlags::INDEX_8BIT;
+        }
+    }
+
+    // Loads P from REP, SEP, PLP or RTI. M and X only exist in native mode,
+    // and making the index registers 8 bits wide clears their high bytes.
+    fn set_status_816(&mut self, status: u8) {
+        let widths = StatusFlags::ACCUMULATOR_8BIT | StatusFlags::INDEX_8BIT;
+        if self.regs816.emulation {
+            let kept = self.status.bits() & widths.bits();
+            self.status = StatusFlags::from_bits((status & !widths.bits()) | kept);
+            return;
+        }
+        self.status = StatusFlags::from_bits(status);
+        if self.status.contains(StatusFlags::INDEX_8BIT) {
+            self.regs816.xh = 0;
+            self.regs816.yh = 0;
+        }
+    }
+
+    fn load_accumulator(&mut self, value: u16) {
+        if self.accumulator_is_wide() {
+            self.set_accumulator(value);
+            self.update_zero_and_negative16(value);
+        } else {
+            self.load_a(value as u8);
+        }
+    }
+
+    fn load_index_x(&mut self, value: u16) {
+        if self.index_is_wide() {
+            self.set_index_x(value);
+            self.update_zero_and_negative16(value);
+        } else {
+            self.load_x(value as u8);
+        }
+    }
+
+    fn load_index_y(&mut self, value: u16) {
+        if self.index_is_wide() {
+            self.set_index_y(value);
+            self.update_zero_and_negative16(value);
+        } else {
+            self.load_y(value as u8);
+        }
+    }
+
+    // 16-bit ALU. Decimal mode corrects each nibble in turn, producing valid
+    // BCD with N and Z taken from the result as on the 65C02.
+    fn adc16(&mut self, value: u16) {
+        let accumulator = self.accumulator();
+        let carry = self.status.contains(StatusFlags::CARRY) as u32;
+        let (result, carry_out) = if self.decimal_mode_active() {
+            let mut result = 0u32;
+            let mut carry = carry;
+            for shift in (0..16).step_by(4) {
+                let mut digit =
+                    (accumulator as u32 >> shift & 0xF) + (value as u32 >> shift & 0xF) + carry;
+                if digit > 0x09 {
+                    digit += 0x06;
+                }
+                carry = (digit > 0x0F) as u32;
+                result |= (digit & 0xF) << shift;
+            }
+            (result as u16, carry != 0)
+        } else {
+            let sum = accumulator as u32 + value as u32 + carry;
+            (sum as u16, sum > 0xFFFF)
+        };
+        self.status.set(
+            StatusFlags::OVERFLOW,
+            (accumulator ^ result) & (value ^ result) & 0x8000 != 0,
+        );
+        self.status.set(StatusFlags::CARRY, carry_out);
+        self.set_accumulator(result);
+        self.update_zero_and_negative16(result);
+    }
+
+    fn sbc16(&mut self, value: u16) {
+        let accumulator = self.accumulator();
+        let borrow = !self.status.contains(StatusFlags::CARRY) as i32;
+        let (result, carry_out) = if self.decimal_mode_active() {
+            let mut result = 0i32;
+            let mut borrow = borrow;
+            for shift in (0..16).step_by(4) {
+                let mut digit =
+                    (accumulator as i32 >> shift & 0xF) - (value as i32 >> shift & 0xF) - borrow;
+                borrow = (digit < 0) as i32;
+                if digit < 0 {
+                    digit -= 0x06;
+                }
+                result |= (digit & 0xF) << shift;
+            }
+            (result as u16, borrow == 0)
+        } else {
+            let difference = accumulator as i32 - value as i32 - borrow;
+            (difference as u16, difference >= 0)
+        };
+        self.status.set(
+            StatusFlags::OVERFLOW,
+            (accumulator ^ value) & (accumulator ^ result) & 0x8000 != 0,
+        );
+        self.status.set(StatusFlags::CARRY, carry_out);
+        self.set_accumulator(result);
+        self.update_zero_and_negative16(result);
+    }
+
+    fn compare16(&mut self, register: u16, value: u16) {
+        self.status.set(StatusFlags::CARRY, register >= value);
+        self.update_zero_and_negative16(register.wrapping_sub(value));
+    }
+
+    fn bit16(&mut self, value: u16) {
+        self.set_zero16(self.accumulator() & value);
+        self.status.set(StatusFlags::NEGATIVE, value & 0x8000 != 0);
+        self.status.set(StatusFlags::OVERFLOW, value & 0x4000 != 0);
+    }
+
+    fn asl16(&mut self, value: u16) -> u16 {
+        self.status.set(StatusFlags::CARRY, value & 0x8000 != 0);
+        let result = value << 1;
+        self.update_zero_and_negative16(result);
+        result
+    }
+
+    fn lsr16(&mut self, value: u16) -> u16 {
+        self.status.set(StatusFlags::CARRY, value & 0x0001 != 0);
+        let result = value >> 1;
+        self.update_zero_and_negative16(result);
+        result
+    }
+
+    fn rol16(&mut self, value: u16) -> u16 {
+        let carry = self.status.contains(StatusFlags::CARRY) as u16;
+        self.status.set(StatusFlags::CARRY, value & 0x8000 != 0);
+        let result = value << 1 | carry;
+        self.update_zero_and_negative16(result);
+        result
+    }
+
+    fn ror16(&mut self, value: u16) -> u16 {
+        let carry = self.status.contains(StatusFlags::CARRY) as u16;
+        self.status.set(StatusFlags::CARRY, value & 0x0001 != 0);
+        let result = value >> 1 | carry << 15;
+        self.update_zero_and_negative16(result);
+        result
+    }
+
+    fn set_zero16(&mut self, value: u16) {
+        self.status.set(StatusFlags::ZERO, value == 0);
+    }
+
+    fn update_zero_and_negative16(&mut self, value: u16) {
+        self.set_zero16(value);
+        self.status.set(StatusFlags::NEGATIVE, value & 0x8000 != 0);
+    }
+
+    // Reads an instruction's operand: immediate data from the program bank,
+    // or data at its effective address
+    fn read_operand_816(&mut self, mode: &AddressingMode, wide: bool) -> u16 {
+        if wide {
+            self.extra_cycles += 1;
+        }
+        if *mode == AddressingMode::Immediate {
+            return if wide {
+                self.fetch_u16_816()
+            } else {
+                self.fetch_816() as u16
+            };
+        }
+        let target = self.effective_address(mode, true);
+        if wide {
+            self.read_u16_816(target)
+        } else {
+            self.memory.read_long(target.address) as u16
+        }
+    }
+
+    // Resolves the data address for `mode`, consuming its operand bytes and
+    // adding the cycles the mode costs beyond the table: one when the direct
+    // page is not page aligned, and for indexed reads one when a page is
+    // crossed or the index registers are 16 bits wide.
+    fn effective_address(&mut self, mode: &AddressingMode, read: bool) -> Target {
+        match mode {
+            AddressingMode::ZeroPage => {
+                let offset = self.fetch_816() as u16;
+                Target::bank0(self.direct_address(offset, 0))
+            }
+            AddressingMode::ZeroPageX => {
+                let offset = self.fetch_816() as u16;
+                Target::bank0(self.direct_address(offset, self.index_x()))
+            }
+            AddressingMode::ZeroPageY => {
+                let offset = self.fetch_816() as u16;
+                Target::bank0(self.direct_address(offset, self.index_y()))
+            }
+            AddressingMode::Absolute => {
+                let address = self.fetch_u16_816();
+                self.data_target(address, 0, false)
+            }
+            AddressingMode::AbsoluteX => {
+                let address = self.fetch_u16_816();
+                self.data_target(address, self.index_x(), read)
+            }
+            AddressingMode::AbsoluteY => {
+                let address = self.fetch_u16_816();
+                self.data_target(address, self.index_y(), read)
+            }
+            AddressingMode::IndirectX => {
+                let offset = self.fetch_816() as u16;
+                let pointer = self.read_direct_u16(offset.wrapping_add(self.index_x()));
+                self.data_target(pointer, 0, false)
+            }
+            AddressingMode::IndirectY => {
+                let offset = self.fetch_816() as u16;
+                let pointer = self.read_direct_u16(offset);
+                self.data_target(pointer, self.index_y(), read)
+            }
+            AddressingMode::ZeroPageIndirect => {
+                let offset = self.fetch_816() as u16;
+                let pointer = self.read_direct_u16(offset);
+                self.data_target(pointer, 0, false)
+            }
+            AddressingMode::ZeroPageIndirectLong => {
+                let offset = self.fetch_816() as u16;
+                Target::long(self.read_direct_u24(offset))
+            }
+            AddressingMode::ZeroPageIndirectLongY => {
+                let offset = self.fetch_816() as u16;
+                let pointer = self.read_direct_u24(offset);
+                Target::long(pointer + self.index_y() as u32)
+            }
+            AddressingMode::AbsoluteLong => Target::long(self.fetch_u24_816()),
+            AddressingMode::AbsoluteLongX => {
+                let address = self.fetch_u24_816();
+                Target::long(address + self.index_x() as u32)
+            }
+            AddressingMode::StackRelative => {
+                let offset = self.fetch_816() as u16;
+                Target::bank0(self.stack_pointer().wrapping_add(offset))
+            }
+            AddressingMode::StackRelativeIndirectY => {
+                let offset = self.fetch_816() as u16;
+                let pointer =
+                    self.read_u16_816(Target::bank0(self.stack_pointer().wrapping_add(offset)));
+                Target::long(
+                    ((self.regs816.dbr as u32) << 16 | pointer as u32) + self.index_y() as u32,
+                )
+            }
+            _ => unreachable!("no operand address for {:?}", mode),
+        }
+    }
+
+    // An address in the data bank, indexed across bank boundaries
+    fn data_target(&mut self, address: u16, index: u16, read: bool) -> Target {
+        let indexed = address.wrapping_add(index);
+        if read && index != 0 && (self.index_is_wide() || indexed & 0xFF00 != address & 0xFF00) {
+            self.extra_cycles += 1;
+        }
+        Target::long(((self.regs816.dbr as u32) << 16 | address as u32) + index as u32)
+    }
+
+    fn program_target(&self, address: u16) -> Target {
+        Target {
+            address: (self.regs816.pbr as u32) << 16 | address as u32,
+            wrap: 0xFFFF,
+        }
+    }
+
+    // Direct page addresses are in bank 0. In emulation mode with the direct
+    // page on a page boundary they wrap within that page, as zero page does
+    // on the 6502.
+    fn direct_address(&mut self, offset: u16, index: u16) -> u16 {
+        let d = self.regs816.d;
+        if d & 0x00FF != 0 {
+            self.extra_cycles += 1;
+        }
+        self.direct_offset(offset.wrapping_add(index))
+    }
+
+    fn direct_offset(&self, offset: u16) -> u16 {
+        let d = self.regs816.d;
+        if self.regs816.emulation && d & 0x00FF == 0 {
+            d | (offset & 0x00FF)
+        } else {
+            d.wrapping_add(offset)
+        }
+    }
+
+    fn read_direct_u16(&mut self, offset: u16) -> u16 {
+        let address = self.direct_address(offset, 0);
+        let low = self.memory.read_long(address as u32) as u16;
+        let high = self
+            .memory
+            .read_long(self.direct_offset(offset.wrapping_add(1)) as u32) as u16;
+        high << 8 | low
+    }
+
+    fn read_direct_u24(&mut self, offset: u16) -> u32 {
+        let low = self.read_direct_u16(offset) as u32;
+        let bank = self
+            .memory
+            .read_long(self.direct_offset(offset.wrapping_add(2)) as u32) as u32;
+        bank << 16 | low
+    }
+
+    fn read_u16_816(&mut self, target: Target) -> u16 {
+        let low = self.memory.read_long(target.address) as u16;
+        let high = self.memory.read_long(target.next()) as u16;
+        high << 8 | low
+    }
+
+    fn read_u24_816(&mut self, pointer: u16) -> u32 {
+        let low = self.read_u16_816(Target::bank0(pointer)) as u32;
+        let bank = self.memory.read_long(pointer.wrapping_add(2) as u32) as u32;
+        bank << 16 | low
+    }
+
+    fn write_u16_816(&mut self, target: Target, value: u16) {
+        self.write_816(target.address, value as u8);
+        self.write_816(target.next(), (value >> 8) as u8);
+    }
+
+    // The exit address is in bank 0
+    fn write_816(&mut self, address: u32, value: u8) {
+        self.memory.write_long(address, value);
+        if address <= 0xFFFF {
+            self.watch_write(address as u16, value);
+        }
+    }
+
+    // Instruction bytes come from the program bank; PC wraps within it
+    fn fetch_816(&mut self) -> u8 {
+        let value = self
+            .memory
+            .read_long((self.regs816.pbr as u32) << 16 | self.pc as u32);
+        self.pc = self.pc.wrapping_add(1);
+        value
+    }
+
+    fn fetch_u16_816(&mut self) -> u16 {
+        let low = self.fetch_816() as u16;
+        let high = self.fetch_816() as u16;
+        high << 8 | low
+    }
+
+    fn fetch_u24_816(&mut self) -> u32 {
+        let low = self.fetch_u16_816() as u32;
+        let bank = self.fetch_816() as u32;
+        bank << 16 | low
+    }
+
+    // The stack is in bank 0. In emulation mode SP wraps within page 1.
+    fn push_816(&mut self, value: u8) {
+        self.write_816(self.stack_pointer() as u32, value);
+        self.set_stack_pointer(self.stack_pointer().wrapping_sub(1));
+    }
+
+    fn pull_816(&mut self) -> u8 {
+        self.set_stack_pointer(self.stack_pointer().wrapping_add(1));
+        self.memory.read_long(self.stack_pointer() as u32)
+    }
+
+    fn push_u16_816(&mut self, value: u16) {
+        self.push_816((value >> 8) as u8);
+        self.push_816(value as u8);
+    }
+
+    fn pull_u16_816(&mut self) -> u16 {
+        let low = self.pull_816() as u16;
+        let high = self.pull_816() as u16;
+        high << 8 | low
+    }
+}
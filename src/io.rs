@@ -0,0 +1,32 @@
+use crate::memory::Memory;
+
+/// A memory-mapped peripheral, such as a timer, UART or video chip. Mounted
+/// on a `MemoryMap` with `add_device`, it sees the CPU's reads and writes
+/// within its window as register accesses.
+pub trait IoDevice {
+    /// Reads the register at `offset` from the start of the device's window.
+    /// Reads may have side effects, such as acknowledging an interrupt.
+    fn read_register(&mut self, offset: u16) -> u8;
+
+    fn write_register(&mut self, offset: u16, value: u8);
+
+    /// Advances the device by `cycles` CPU clock cycles.
+    fn tick(&mut self, _cycles: u8) {}
+
+    /// Whether the device's interrupt output is asserting IRQ.
+    fn irq(&self) -> bool {
+        false
+    }
+}
+
+/// Plain memory as a device, for RAM or buffers that sit in a window of
+/// their own.
+impl IoDevice for Memory {
+    fn read_register(&mut self, offset: u16) -> u8 {
+        self.read(offset)
+    }
+
+    fn write_register(&mut self, offset: u16, value: u8) {
+        self.write(offset, value);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod assembler;
+pub mod bus;
+pub mod cpu;
+pub mod io;
+pub mod mapper;
+pub mod memory;
+pub mod memory_map;
+mod random;
+pub mod variant;
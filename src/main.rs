@@ -1,50 +1,121 @@
 use std::env;
 use std::fs;
 use std::process;
+use std::str::FromStr;
 
-mod assembler;
-mod cpu;
-mod memory;
-
-use cpu::CPU;
-use memory::Memory;
+use rs6502::assembler;
+use rs6502::cpu::{CPU, CpuError, HaltReason, StopReason};
+use rs6502::memory::{LONG_ADDRESS_SPACE, Memory};
+use rs6502::variant::CpuVariant;
 
 const PROGRAM_START_ADDRESS: u16 = 0x0600; // Common starting address for programs
+const DEFAULT_MAX_CYCLES: u64 = 100_000_000; // Stops runaway programs after a minute or so at 1MHz
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: {} <assembly_file>", args[0]);
-        process::exit(1);
-    }
+    let usage = format!(
+        "Usage: {} [--cpu 6502|6502-undocumented|65c02|65816|2a03] [--max-cycles <n>] [--random-state <seed>] <assembly_file>",
+        args[0]
+    );
 
-    let assembly_file = &args[1];
+    let mut variant = CpuVariant::default();
+    let mut max_cycles = DEFAULT_MAX_CYCLES;
+    let mut random_seed = None;
+    let mut assembly_file = None;
+    let mut options = args.iter().skip(1);
+    while let Some(arg) = options.next() {
+        match arg.as_str() {
+            "--cpu" => {
+                let name = options.next().map(String::as_str).unwrap_or_default();
+                variant = CpuVariant::from_str(name).unwrap_or_else(|_| {
+                    eprintln!("Unknown CPU variant: {}", name);
+                    eprintln!("{}", usage);
+                    process::exit(1);
+                });
+            }
+            "--max-cycles" => {
+                let count = options.next().map(String::as_str).unwrap_or_default();
+                max_cycles = count.parse().unwrap_or_else(|_| {
+                    eprintln!("Invalid cycle count: {}", count);
+                    eprintln!("{}", usage);
+                    process::exit(1);
+                });
+            }
+            "--random-state" => {
+                let seed = options.next().map(String::as_str).unwrap_or_default();
+                random_seed = Some(seed.parse().unwrap_or_else(|_| {
+                    eprintln!("Invalid seed: {}", seed);
+                    eprintln!("{}", usage);
+                    process::exit(1);
+                }));
+            }
+            _ => assembly_file = Some(arg),
+        }
+    }
+    let Some(assembly_file) = assembly_file else {
+        eprintln!("{}", usage);
+        process::exit(1);
+    };
 
     let assembly_code = fs::read_to_string(assembly_file).expect("Failed to read assembly file");
 
-    let machine_code = assembler::assemble(&assembly_code);
+    let machine_code = assembler::assemble_for(&assembly_code, variant);
     println!("Machine code: {:02X?}", machine_code);
 
-    let mut memory = Memory::new();
+    // The 65C816 reaches 16MB through its bank registers and long addressing
+    let mut memory = if variant.is_65816() {
+        Memory::with_size(LONG_ADDRESS_SPACE)
+    } else {
+        Memory::new()
+    };
+    if let Some(seed) = random_seed {
+        memory.randomize(seed);
+    }
     memory.load_program(machine_code, PROGRAM_START_ADDRESS);
 
     memory.write_u16(0xFFFC, PROGRAM_START_ADDRESS);
 
-    let mut cpu = CPU::new(memory);
+    let mut cpu = CPU::with_variant(memory, variant);
+    if let Some(seed) = random_seed {
+        cpu.randomize_registers(seed);
+    }
+    cpu.halt_conditions.brk = true; // Programs end with BRK
+    cpu.halt_conditions.top_level_rts = true; // Programs may end with a bare RTS
+    cpu.halt_conditions.cycle_budget = Some(max_cycles);
     cpu.reset();
 
     println!("Starting execution...");
     loop {
         println!(
-            "PC: {:04X}, A: {:02X}, X: {:02X}, Y: {:02X}, SP: {:02X}, Status: {:02X}",
-            cpu.pc, cpu.a, cpu.x, cpu.y, cpu.sp, cpu.status
+            "PC: {:04X}, A: {:02X}, X: {:02X}, Y: {:02X}, SP: {:02X}, Status: {:02X} {}",
+            cpu.pc,
+            cpu.a,
+            cpu.x,
+            cpu.y,
+            cpu.sp,
+            cpu.status.bits(),
+            cpu.status
         );
 
-        cpu.execute_instruction();
-
-        if cpu.halted {
-            println!("Execution halted. Final accumulator value: {}", cpu.a);
-            process::exit(cpu.a as i32); // Use accumulator value as exit code
+        let error = match cpu.run_for_instructions(1).stop {
+            StopReason::Halted(error) => error,
+            StopReason::Waiting => {
+                // Nothing here raises IRQ or NMI, so WAI would never return
+                eprintln!("Execution stopped: waiting for an interrupt after WAI");
+                process::exit(1);
+            }
+            _ => continue,
+        };
+        match error {
+            CpuError::Halted(reason @ (HaltReason::Brk | HaltReason::TopLevelRts)) => {
+                println!("Halted by {:?}", reason);
+                println!("Execution halted. Final accumulator value: {}", cpu.a);
+                process::exit(cpu.a as i32); // Use accumulator value as exit code
+            }
+            error => {
+                eprintln!("Execution stopped: {}", error);
+                process::exit(1);
+            }
         }
     }
 }
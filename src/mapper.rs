@@ -0,0 +1,330 @@
+use std::fmt;
+
+use crate::bus::Bus;
+use crate::memory::Memory;
+
+/// Where a CPU write ends up.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WriteTarget {
+    /// The address is not banked for writes; the write reaches `Memory`.
+    Memory,
+    /// This offset in the backing store, for banked RAM.
+    Store(usize),
+    /// The address shows ROM, so the write is lost.
+    Dropped,
+}
+
+/// A mapper's control registers, from which it works out every window.
+/// It is plain data, so it can be kept with a snapshot of the machine and
+/// handed back to `Mapper::restore_bank_state` later.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Hash)]
+pub struct BankState {
+    pub registers: Vec<u8>,
+}
+
+impl BankState {
+    // The registers of a mapper that saves `N`, or an error for a state
+    // holding some other number, such as a default or another mapper's
+    fn registers<const N: usize>(&self) -> Result<[u8; N], BankStateError> {
+        self.registers
+            .as_slice()
+            .try_into()
+            .map_err(|_| BankStateError {
+                expected: N,
+                found: self.registers.len(),
+            })
+    }
+}
+
+/// Why `Mapper::restore_bank_state` rejected a `BankState`: it does not hold
+/// the number of registers the mapper saves.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BankStateError {
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl fmt::Display for BankStateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "bank state has {} registers, expected {}",
+            self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for BankStateError {}
+
+/// Bank switching logic: which part of a backing store larger than the
+/// address space each window currently shows, and the control registers
+/// that change it.
+pub trait Mapper {
+    /// Offered every CPU write before it goes anywhere else. Updates the
+    /// bank selection if the write hits a control register, and returns
+    /// whether the write stops there.
+    fn write_register(&mut self, address: u16, value: u8) -> bool;
+
+    /// Where a read of `address` comes from in the backing store, or `None`
+    /// to read `Memory` as usual.
+    fn map_read(&self, address: u16) -> Option<usize>;
+
+    /// Where a write to `address` goes. By default banked windows are ROM
+    /// and everything else is `Memory`.
+    fn map_write(&self, address: u16) -> WriteTarget {
+        match self.map_read(address) {
+            Some(_) => WriteTarget::Dropped,
+            None => WriteTarget::Memory,
+        }
+    }
+
+    fn bank_state(&self) -> BankState;
+
+    /// Restores a state from `bank_state`, leaving the mapper as it was if
+    /// the state does not fit it.
+    fn restore_bank_state(&mut self, state: &BankState) -> Result<(), BankStateError>;
+}
+
+/// A bus of 64KB `Memory` with a mapper paging a larger backing store into
+/// windows over it. Offsets past the end of the store read as $FF, like an
+/// empty socket.
+pub struct BankedMemory<M> {
+    pub memory: Memory,
+    pub store: Vec<u8>,
+    pub mapper: M,
+}
+
+impl<M: Mapper> BankedMemory<M> {
+    pub fn new(mapper: M, store: Vec<u8>) -> Self {
+        BankedMemory {
+            memory: Memory::new(),
+            store,
+            mapper,
+        }
+    }
+
+    /// Copies `program` into the unbanked `Memory` at `start_address`.
+    pub fn load_program(&mut self, program: Vec<u8>, start_address: u16) {
+        self.memory.load_program(program, start_address);
+    }
+
+    pub fn bank_state(&self) -> BankState {
+        self.mapper.bank_state()
+    }
+
+    pub fn restore_bank_state(&mut self, state: &BankState) -> Result<(), BankStateError> {
+        self.mapper.restore_bank_state(state)
+    }
+}
+
+impl<M: Mapper> Bus for BankedMemory<M> {
+    fn read(&mut self, address: u16) -> u8 {
+        match self.mapper.map_read(address) {
+            Some(offset) => self.store.get(offset).copied().unwrap_or(0xFF),
+            None => self.memory.read(address),
+        }
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        if self.mapper.write_register(address, value) {
+            return;
+        }
+        match self.mapper.map_write(address) {
+            WriteTarget::Memory => self.memory.write(address, value),
+            WriteTarget::Store(offset) => {
+                if let Some(byte) = self.store.get_mut(offset) {
+                    *byte = value;
+                }
+            }
+            WriteTarget::Dropped => {}
+        }
+    }
+}
+
+const NES_BANK_SIZE: usize = 0x4000;
+
+/// The NES UxROM boards: a switchable 16KB bank at $8000-$BFFF and the last
+/// bank fixed at $C000-$FFFF. Writing anywhere in $8000-$FFFF selects the
+/// switchable bank. The backing store is the PRG ROM.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Uxrom {
+    banks: usize,
+    bank: u8,
+}
+
+impl Uxrom {
+    /// A mapper for a PRG ROM of `banks` 16KB banks.
+    pub fn new(banks: usize) -> Self {
+        assert!(banks > 0, "UxROM needs at least one bank");
+        Uxrom { banks, bank: 0 }
+    }
+}
+
+impl Mapper for Uxrom {
+    fn write_register(&mut self, address: u16, value: u8) -> bool {
+        if address >= 0x8000 {
+            self.bank = value;
+        }
+        address >= 0x8000
+    }
+
+    fn map_read(&self, address: u16) -> Option<usize> {
+        let offset = address as usize & (NES_BANK_SIZE - 1);
+        match address {
+            0x8000..=0xBFFF => Some((self.bank as usize % self.banks) * NES_BANK_SIZE + offset),
+            0xC000..=0xFFFF => Some((self.banks - 1) * NES_BANK_SIZE + offset),
+            _ => None,
+        }
+    }
+
+    fn bank_state(&self) -> BankState {
+        BankState {
+            registers: vec![self.bank],
+        }
+    }
+
+    fn restore_bank_state(&mut self, state: &BankState) -> Result<(), BankStateError> {
+        [self.bank] = state.registers()?;
+        Ok(())
+    }
+}
+
+const SIDEWAYS_SLOT_SIZE: usize = 0x4000;
+
+/// BBC Micro sideways ROM: sixteen 16KB slots sharing $8000-$BFFF, selected
+/// by the low four bits of ROMSEL at $FE30 (mirrored to $FE33). Slot `n` is
+/// at `n * 16KB` in the backing store, and slots marked as sideways RAM
+/// take writes.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SidewaysRom {
+    ram_slots: u16, // Bit n set when slot n holds RAM
+    romsel: u8,
+}
+
+impl SidewaysRom {
+    pub fn new() -> Self {
+        SidewaysRom {
+            ram_slots: 0,
+            romsel: 0,
+        }
+    }
+
+    /// Makes `slot` writable sideways RAM.
+    pub fn with_ram_slot(mut self, slot: u8) -> Self {
+        self.ram_slots |= 1 << (slot & 0x0F);
+        self
+    }
+
+    fn slot(&self) -> u8 {
+        self.romsel & 0x0F
+    }
+}
+
+impl Default for SidewaysRom {
+    fn default() -> Self {
+        SidewaysRom::new()
+    }
+}
+
+impl Mapper for SidewaysRom {
+    fn write_register(&mut self, address: u16, value: u8) -> bool {
+        let romsel = (0xFE30..=0xFE33).contains(&address);
+        if romsel {
+            self.romsel = value;
+        }
+        romsel
+    }
+
+    fn map_read(&self, address: u16) -> Option<usize> {
+        (0x8000..=0xBFFF)
+            .contains(&address)
+            .then(|| self.slot() as usize * SIDEWAYS_SLOT_SIZE + (address as usize - 0x8000))
+    }
+
+    fn map_write(&self, address: u16) -> WriteTarget {
+        match self.map_read(address) {
+            Some(offset) if self.ram_slots & (1 << self.slot()) != 0 => WriteTarget::Store(offset),
+            Some(_) => WriteTarget::Dropped,
+            None => WriteTarget::Memory,
+        }
+    }
+
+    fn bank_state(&self) -> BankState {
+        BankState {
+            registers: vec![self.romsel],
+        }
+    }
+
+    fn restore_bank_state(&mut self, state: &BankState) -> Result<(), BankStateError> {
+        [self.romsel] = state.registers()?;
+        Ok(())
+    }
+}
+
+// C64 processor port bits that drive the PLA
+const LORAM: u8 = 0b001;
+const HIRAM: u8 = 0b010;
+const CHAREN: u8 = 0b100;
+
+/// The ROM banking of the Commodore 64, driven by the processor port at $01.
+/// The backing store holds BASIC (8KB at 0), KERNAL (8KB at $2000) and the
+/// character ROM (4KB at $4000). Where ROM is visible writes still reach the
+/// RAM underneath; where the character ROM is not, $D000-$DFFF is left to
+/// `Memory`, which stands in for the I/O area. The port's data direction
+/// register at $00 is not modelled.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct C64Banking {
+    port: u8,
+}
+
+impl C64Banking {
+    /// The power-on configuration, with BASIC, KERNAL and I/O visible.
+    pub fn new() -> Self {
+        C64Banking { port: 0x37 }
+    }
+}
+
+impl Default for C64Banking {
+    fn default() -> Self {
+        C64Banking::new()
+    }
+}
+
+impl Mapper for C64Banking {
+    // The port is also written through to the RAM at $01
+    fn write_register(&mut self, address: u16, value: u8) -> bool {
+        if address == 0x0001 {
+            self.port = value;
+        }
+        false
+    }
+
+    fn map_read(&self, address: u16) -> Option<usize> {
+        let loram = self.port & LORAM != 0;
+        let hiram = self.port & HIRAM != 0;
+        let charen = self.port & CHAREN != 0;
+        match address {
+            0xA000..=0xBFFF if loram && hiram => Some(address as usize - 0xA000),
+            0xD000..=0xDFFF if !charen && (loram || hiram) => {
+                Some(0x4000 + address as usize - 0xD000)
+            }
+            0xE000..=0xFFFF if hiram => Some(0x2000 + address as usize - 0xE000),
+            _ => None,
+        }
+    }
+
+    fn map_write(&self, _address: u16) -> WriteTarget {
+        WriteTarget::Memory
+    }
+
+    fn bank_state(&self) -> BankState {
+        BankState {
+            registers: vec![self.port],
+        }
+    }
+
+    fn restore_bank_state(&mut self, state: &BankState) -> Result<(), BankStateError> {
+        [self.port] = state.registers()?;
+        Ok(())
+    }
+}
@@ -1,6 +1,28 @@
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+use crate::bus::Bus;
+use crate::random::Xorshift;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+mod hooks;
+
+pub use hooks::{Access, AccessKind, HookAction, HookId};
+use hooks::{Hook, Hooks};
+
+/// Size of the 6502's 16-bit address space.
+pub const ADDRESS_SPACE: usize = 0x1_0000;
+/// Size of the 65C816's 24-bit address space.
+pub const LONG_ADDRESS_SPACE: usize = 0x100_0000;
+
+/// Flat RAM, and the default bus for the CPU.
+///
+/// Hooks added with `add_hook` see the accesses the CPU makes through `Bus`.
+/// Calling `read` and `write` directly bypasses them, as a debugger would.
+/// Clones and comparisons look at the contents alone: a clone has no hooks.
 pub struct Memory {
-    data: [u8; 65536], // 64KB of memory
+    data: Vec<u8>,             // 64KB of memory, or up to 16MB for the 65C816
+    hooks: Option<Box<Hooks>>, // None until a hook is added, keeping the bus path to one check
+    next_hook_id: u64,
 }
 
 impl Default for Memory {
@@ -11,8 +33,20 @@ impl Default for Memory {
 
 impl Memory {
     pub fn new() -> Self {
+        Memory::with_size(ADDRESS_SPACE)
+    }
+
+    /// Memory of `size` bytes, a power of two of at least 64KB. Long
+    /// addresses beyond the end mirror the memory that exists.
+    pub fn with_size(size: usize) -> Self {
+        assert!(
+            size.is_power_of_two() && (ADDRESS_SPACE..=LONG_ADDRESS_SPACE).contains(&size),
+            "memory size must be a power of two between 64KB and 16MB"
+        );
         Memory {
-            data: [0; 65536], // Initialize memory to zero
+            data: vec![0; size], // Initialize memory to zero
+            hooks: None,
+            next_hook_id: 0,
         }
     }
 
@@ -24,20 +58,175 @@ impl Memory {
         self.data[address as usize] = value;
     }
 
+    /// Reads from a 24-bit address, as used by the 65C816.
+    pub fn read_long(&self, address: u32) -> u8 {
+        self.data[address as usize & (self.data.len() - 1)]
+    }
+
+    /// Writes to a 24-bit address, as used by the 65C816.
+    pub fn write_long(&mut self, address: u32, value: u8) {
+        let mask = self.data.len() - 1;
+        self.data[address as usize & mask] = value;
+    }
+
     pub fn read_u16(&self, address: u16) -> u16 {
         let low = self.read(address) as u16;
-        let high = self.read(address + 1) as u16;
+        let high = self.read(address.wrapping_add(1)) as u16;
         (high << 8) | low
     }
 
     pub fn write_u16(&mut self, address: u16, value: u16) {
         self.write(address, (value & 0x00FF) as u8);
-        self.write(address + 1, (value >> 8) as u8);
+        self.write(address.wrapping_add(1), (value >> 8) as u8);
     }
 
+    /// Copies `program` to `start_address`, wrapping past $FFFF to $0000.
     pub fn load_program(&mut self, program: Vec<u8>, start_address: u16) {
-        let start = start_address as usize;
-        let end = start + program.len();
-        self.data[start..end].copy_from_slice(&program);
+        let mut address = start_address;
+        for byte in program {
+            self.write(address, byte);
+            address = address.wrapping_add(1);
+        }
+    }
+
+    /// Fills all of memory with pseudo-random bytes drawn from `seed`, as RAM
+    /// holds after power-on. Do this before loading programs and vectors;
+    /// software that reads memory it never wrote then sees garbage rather
+    /// than convenient zeros.
+    pub fn randomize(&mut self, seed: u64) {
+        let mut rng = Xorshift::new(seed);
+        self.data.fill_with(|| rng.next_u8());
+    }
+
+    /// Calls `hook` on every CPU access of `kind` to an address in `range`,
+    /// after the hooks added before it. A single address is a range of one.
+    pub fn add_hook(
+        &mut self,
+        kind: AccessKind,
+        range: RangeInclusive<u16>,
+        hook: impl FnMut(&Access) -> HookAction + Send + 'static,
+    ) -> HookId {
+        let id = HookId(self.next_hook_id);
+        self.next_hook_id += 1;
+        self.hooks.get_or_insert_default().hooks.push(Hook {
+            id,
+            kind,
+            range,
+            callback: Box::new(hook),
+        });
+        id
+    }
+
+    /// Removes a hook, returning whether it was registered.
+    pub fn remove_hook(&mut self, id: HookId) -> bool {
+        let Some(hooks) = &mut self.hooks else {
+            return false;
+        };
+        let count = hooks.hooks.len();
+        hooks.hooks.retain(|hook| hook.id != id);
+        let removed = hooks.hooks.len() < count;
+        if hooks.hooks.is_empty() {
+            self.hooks = None;
+        }
+        removed
+    }
+
+    pub fn clear_hooks(&mut self) {
+        self.hooks = None;
+    }
+}
+
+impl Clone for Memory {
+    fn clone(&self) -> Self {
+        Memory {
+            data: self.data.clone(),
+            hooks: None,
+            next_hook_id: self.next_hook_id,
+        }
+    }
+}
+
+impl PartialEq for Memory {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl Eq for Memory {}
+
+impl PartialOrd for Memory {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Memory {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.data.cmp(&other.data)
+    }
+}
+
+impl fmt::Debug for Memory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hooks = self.hooks.as_ref().map_or(0, |hooks| hooks.hooks.len());
+        f.debug_struct("Memory")
+            .field("data", &self.data)
+            .field("hooks", &hooks)
+            .finish()
+    }
+}
+
+impl Bus for Memory {
+    fn read(&mut self, address: u16) -> u8 {
+        let value = Memory::read(self, address);
+        match &mut self.hooks {
+            None => value,
+            Some(hooks) => hooks.run(AccessKind::Read, address, value).unwrap_or(0xFF),
+        }
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        let value = match &mut self.hooks {
+            None => Some(value),
+            Some(hooks) => hooks.run(AccessKind::Write, address, value),
+        };
+        if let Some(value) = value {
+            Memory::write(self, address, value);
+        }
+    }
+
+    fn fetch(&mut self, address: u16) -> u8 {
+        let value = Memory::read(self, address);
+        match &mut self.hooks {
+            None => value,
+            Some(hooks) => {
+                hooks.pc = address;
+                hooks
+                    .run(AccessKind::Execute, address, value)
+                    .unwrap_or(0xFF)
+            }
+        }
+    }
+
+    // Hooks cover bank 0 of the 65C816's address space
+    fn read_long(&mut self, address: u32) -> u8 {
+        match self.hooks {
+            Some(_) if address <= 0xFFFF => Bus::read(self, address as u16),
+            _ => Memory::read_long(self, address),
+        }
+    }
+
+    fn write_long(&mut self, address: u32, value: u8) {
+        match self.hooks {
+            Some(_) if address <= 0xFFFF => Bus::write(self, address as u16, value),
+            _ => Memory::write_long(self, address, value),
+        }
+    }
+
+    fn fetch_long(&mut self, address: u32) -> u8 {
+        match self.hooks {
+            Some(_) if address <= 0xFFFF => Bus::fetch(self, address as u16),
+            _ => Memory::read_long(self, address),
+        }
     }
 }
@@ -0,0 +1,81 @@
+use std::ops::RangeInclusive;
+
+/// The kind of access a hook is called for.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum AccessKind {
+    Read,
+    Write,
+    /// The fetch of an instruction's opcode.
+    Execute,
+}
+
+/// An access as seen by a hook.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Access {
+    pub address: u16,
+    /// The byte read or fetched, or the byte being written. A hook sees any
+    /// replacement made by the hooks before it.
+    pub value: u8,
+    /// Where the instruction making the access starts, as of the last
+    /// opcode fetch.
+    pub pc: u16,
+    pub kind: AccessKind,
+}
+
+/// What a hook does with an access.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum HookAction {
+    /// Let the access go ahead.
+    Continue,
+    /// Read or write this value instead.
+    Replace(u8),
+    /// Stop the access. A vetoed write leaves memory alone and a vetoed read
+    /// or fetch sees $FF, as from an empty socket. Later hooks are skipped.
+    Veto,
+}
+
+/// Identifies a hook for `Memory::remove_hook`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct HookId(pub(super) u64);
+
+// Send, so a CPU with hooks on its memory can move to another thread
+pub(super) type HookFn = Box<dyn FnMut(&Access) -> HookAction + Send>;
+
+pub(super) struct Hook {
+    pub(super) id: HookId,
+    pub(super) kind: AccessKind,
+    pub(super) range: RangeInclusive<u16>,
+    pub(super) callback: HookFn,
+}
+
+// The hooks registered on a memory, and the PC to report to them
+#[derive(Default)]
+pub(super) struct Hooks {
+    pub(super) hooks: Vec<Hook>,
+    pub(super) pc: u16,
+}
+
+impl Hooks {
+    // Runs the hooks matching an access in the order they were added, and
+    // returns the value to go ahead with, or None if one vetoed it
+    pub(super) fn run(&mut self, kind: AccessKind, address: u16, value: u8) -> Option<u8> {
+        let mut access = Access {
+            address,
+            value,
+            pc: self.pc,
+            kind,
+        };
+        let matching = self
+            .hooks
+            .iter_mut()
+            .filter(|hook| hook.kind == kind && hook.range.contains(&address));
+        for hook in matching {
+            match (hook.callback)(&access) {
+                HookAction::Continue => {}
+                HookAction::Replace(value) => access.value = value,
+                HookAction::Veto => return None,
+            }
+        }
+        Some(access.value)
+    }
+}
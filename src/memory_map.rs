@@ -0,0 +1,197 @@
+use crate::bus::Bus;
+use crate::io::IoDevice;
+use crate::memory::Memory;
+use std::ops::RangeInclusive;
+
+/// What a write to ROM does.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RomWrites {
+    /// The write is dropped, as on real hardware.
+    Ignore,
+    /// The write is dropped and recorded in `MemoryMap::rom_writes`, to
+    /// catch firmware that scribbles over itself.
+    Log,
+}
+
+/// How a region of the address space behaves.
+pub enum RegionKind {
+    /// Readable and writable memory.
+    Ram,
+    /// Read-only memory. Contents are put in place with
+    /// `MemoryMap::load_program`, which is not subject to the protection.
+    Rom(RomWrites),
+    /// Repeats `source` across the region, as an incompletely decoded chip
+    /// does: 2KB of RAM at $0000 mirrored up to $1FFF is
+    /// `Mirror(0x0000..=0x07FF)` on $0800-$1FFF.
+    Mirror(RangeInclusive<u16>),
+    /// Nothing answers here. Reads see open bus, the last value on the data
+    /// bus, and writes are lost.
+    Unmapped,
+    /// A device window. The device sees addresses relative to the start of
+    /// the region as register offsets.
+    Device(Box<dyn IoDevice>),
+}
+
+struct Region {
+    range: RangeInclusive<u16>,
+    kind: RegionKind,
+}
+
+// Where an access ends up once mirrors are followed
+enum Target {
+    Ram(u16),
+    Rom(u16, RomWrites),
+    Unmapped,
+    Device(usize, u16),
+}
+
+/// A bus whose address space is put together from regions declared by
+/// address range, over a 64KB `Memory` that holds the RAM and ROM contents.
+/// Regions added later take precedence where they overlap earlier ones, and
+/// addresses no region covers are unmapped.
+pub struct MemoryMap {
+    memory: Memory,
+    regions: Vec<Region>,
+    open_bus: u8,
+    rom_writes: Vec<(u16, u8)>,
+}
+
+impl Default for MemoryMap {
+    fn default() -> Self {
+        MemoryMap::new()
+    }
+}
+
+impl MemoryMap {
+    /// A memory map with nothing mapped.
+    pub fn new() -> Self {
+        MemoryMap {
+            memory: Memory::new(),
+            regions: Vec::new(),
+            open_bus: 0,
+            rom_writes: Vec::new(),
+        }
+    }
+
+    pub fn add_region(&mut self, range: RangeInclusive<u16>, kind: RegionKind) {
+        if let RegionKind::Mirror(source) = &kind {
+            assert!(!source.is_empty(), "mirror of an empty range");
+        }
+        self.regions.push(Region { range, kind });
+    }
+
+    pub fn add_ram(&mut self, range: RangeInclusive<u16>) {
+        self.add_region(range, RegionKind::Ram);
+    }
+
+    pub fn add_rom(&mut self, range: RangeInclusive<u16>, writes: RomWrites) {
+        self.add_region(range, RegionKind::Rom(writes));
+    }
+
+    pub fn add_mirror(&mut self, range: RangeInclusive<u16>, source: RangeInclusive<u16>) {
+        self.add_region(range, RegionKind::Mirror(source));
+    }
+
+    pub fn add_unmapped(&mut self, range: RangeInclusive<u16>) {
+        self.add_region(range, RegionKind::Unmapped);
+    }
+
+    pub fn add_device(&mut self, range: RangeInclusive<u16>, device: impl IoDevice + 'static) {
+        self.add_region(range, RegionKind::Device(Box::new(device)));
+    }
+
+    /// Copies `program` into the backing memory at `start_address`,
+    /// regardless of what is mapped there. This is how ROM gets its contents.
+    pub fn load_program(&mut self, program: Vec<u8>, start_address: u16) {
+        self.memory.load_program(program, start_address);
+    }
+
+    /// The backing store for RAM and ROM, addressed without the map.
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut Memory {
+        &mut self.memory
+    }
+
+    /// Writes made to ROM regions set to `RomWrites::Log`, oldest first.
+    pub fn rom_writes(&self) -> &[(u16, u8)] {
+        &self.rom_writes
+    }
+
+    pub fn clear_rom_writes(&mut self) {
+        self.rom_writes.clear();
+    }
+
+    // Follows mirrors to the region that answers for `address`. A mirror
+    // chain longer than the number of regions must loop, and is unmapped.
+    fn resolve(&self, mut address: u16) -> Target {
+        for _ in 0..=self.regions.len() {
+            let Some((index, region)) = self
+                .regions
+                .iter()
+                .enumerate()
+                .rev()
+                .find(|(_, region)| region.range.contains(&address))
+            else {
+                return Target::Unmapped;
+            };
+            let offset = address - region.range.start();
+            match &region.kind {
+                RegionKind::Ram => return Target::Ram(address),
+                RegionKind::Rom(writes) => return Target::Rom(address, *writes),
+                RegionKind::Unmapped => return Target::Unmapped,
+                RegionKind::Device(_) => return Target::Device(index, offset),
+                RegionKind::Mirror(source) => {
+                    let size = (source.end() - source.start()) as u32 + 1;
+                    address = source.start() + (offset as u32 % size) as u16;
+                }
+            }
+        }
+        Target::Unmapped
+    }
+
+    fn device(&mut self, index: usize) -> &mut dyn IoDevice {
+        match &mut self.regions[index].kind {
+            RegionKind::Device(device) => device.as_mut(),
+            _ => unreachable!("region {} is not a device", index),
+        }
+    }
+}
+
+impl Bus for MemoryMap {
+    fn read(&mut self, address: u16) -> u8 {
+        let value = match self.resolve(address) {
+            Target::Ram(address) | Target::Rom(address, _) => self.memory.read(address),
+            Target::Unmapped => self.open_bus,
+            Target::Device(index, offset) => self.device(index).read_register(offset),
+        };
+        self.open_bus = value;
+        value
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        self.open_bus = value;
+        match self.resolve(address) {
+            Target::Ram(address) => self.memory.write(address, value),
+            Target::Rom(address, RomWrites::Log) => self.rom_writes.push((address, value)),
+            Target::Rom(_, RomWrites::Ignore) | Target::Unmapped => {}
+            Target::Device(index, offset) => self.device(index).write_register(offset, value),
+        }
+    }
+
+    fn tick(&mut self, cycles: u8) {
+        for region in &mut self.regions {
+            if let RegionKind::Device(device) = &mut region.kind {
+                device.tick(cycles);
+            }
+        }
+    }
+
+    fn irq(&self) -> bool {
+        self.regions
+            .iter()
+            .any(|region| matches!(&region.kind, RegionKind::Device(device) if device.irq()))
+    }
+}
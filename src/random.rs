@@ -0,0 +1,18 @@
+/// Small xorshift generator for randomized power-on state. Runs are
+/// reproducible from the seed, and no dependency is needed for it.
+pub(crate) struct Xorshift(u64);
+
+impl Xorshift {
+    pub(crate) fn new(seed: u64) -> Self {
+        // The state must never be zero, and nearby seeds should not give
+        // nearby sequences
+        Xorshift(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub(crate) fn next_u8(&mut self) -> u8 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 32) as u8
+    }
+}
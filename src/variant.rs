@@ -0,0 +1,77 @@
+use strum_macros::EnumString;
+
+use crate::assembler::{
+    CMOS_LOOKUP, INSTRUCTION_LOOKUP, Instruction, UNDOCUMENTED_LOOKUP, W65C816_LOOKUP,
+};
+
+/// The member of the 6502 family being emulated or assembled for. Parses
+/// from the names used on the command line, such as `65c02` or `2a03`.
+#[derive(EnumString, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[strum(ascii_case_insensitive)]
+pub enum CpuVariant {
+    /// NMOS 6502 decoding only the 151 documented opcodes.
+    #[default]
+    #[strum(serialize = "6502")]
+    Nmos6502,
+    /// NMOS 6502 that also decodes the undocumented opcodes, as many
+    /// commercial programs expect.
+    #[strum(serialize = "6502-undocumented")]
+    Nmos6502Undocumented,
+    /// WDC 65C02, with the CMOS instruction set and bug fixes.
+    #[strum(serialize = "65c02")]
+    Cmos65C02,
+    /// WDC 65C816, starting in 6502 emulation mode with XCE switching to the
+    /// 16-bit native mode.
+    #[strum(serialize = "65816")]
+    W65C816,
+    /// Ricoh 2A03/2A07 of the NES: an NMOS 6502 core, undocumented opcodes
+    /// included, with the decimal mode circuitry cut out.
+    #[strum(serialize = "2a03")]
+    Ricoh2A03,
+}
+
+impl CpuVariant {
+    /// Whether the undocumented NMOS opcodes are decoded.
+    pub fn supports_undocumented(self) -> bool {
+        matches!(
+            self,
+            CpuVariant::Nmos6502Undocumented | CpuVariant::Ricoh2A03
+        )
+    }
+
+    /// Whether ADC and SBC honour the D flag. The 2A03 still lets SED and
+    /// CLD change the flag, but its ALU always works in binary.
+    pub fn has_decimal_mode(self) -> bool {
+        !matches!(self, CpuVariant::Ricoh2A03)
+    }
+
+    /// Whether this is a CMOS part, which fixes the NMOS bugs and clears D
+    /// when taking an interrupt.
+    pub fn is_cmos(self) -> bool {
+        matches!(self, CpuVariant::Cmos65C02 | CpuVariant::W65C816)
+    }
+
+    /// Whether this is the 65C816, with its 24-bit address space.
+    pub fn is_65816(self) -> bool {
+        matches!(self, CpuVariant::W65C816)
+    }
+
+    /// Opcodes this variant decodes on top of, or in place of, the
+    /// documented NMOS set.
+    pub fn extension(self) -> Option<&'static phf::Map<u8, Instruction>> {
+        match self {
+            CpuVariant::Nmos6502 => None,
+            CpuVariant::Nmos6502Undocumented | CpuVariant::Ricoh2A03 => Some(&UNDOCUMENTED_LOOKUP),
+            CpuVariant::Cmos65C02 => Some(&CMOS_LOOKUP),
+            CpuVariant::W65C816 => Some(&W65C816_LOOKUP),
+        }
+    }
+
+    /// Decodes `opcode`, returning `None` for bytes this variant does not
+    /// implement.
+    pub fn instruction(self, opcode: u8) -> Option<&'static Instruction> {
+        self.extension()
+            .and_then(|extension| extension.get(&opcode))
+            .or_else(|| INSTRUCTION_LOOKUP.get(&opcode))
+    }
+}
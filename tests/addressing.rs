@@ -0,0 +1,200 @@
+// Operand decoding: every instruction leaves PC on the next opcode, and
+// effective addresses carry across page boundaries where the hardware does.
+// The zero page and JMP (abs) wraps are in page_wrap.rs.
+
+mod common;
+
+use common::{START, cpu_for, cpu_with, cpu_with_program};
+use rs6502::assembler::{AddressingMode, OpCode};
+use rs6502::cpu::CPU;
+use rs6502::variant::CpuVariant;
+
+fn run(cpu: &mut CPU, instructions: usize) {
+    for _ in 0..instructions {
+        cpu.execute_instruction().unwrap();
+    }
+}
+
+// With zeroed operands a branch lands on the next instruction whether or not
+// it is taken, so only instructions that jump or stop are left out
+fn falls_through(op: OpCode) -> bool {
+    !matches!(
+        op,
+        OpCode::JMP
+            | OpCode::JSR
+            | OpCode::RTS
+            | OpCode::RTI
+            | OpCode::BRK
+            | OpCode::KIL
+            | OpCode::STP
+    )
+}
+
+#[test]
+fn pc_advances_by_instruction_length() {
+    for variant in [CpuVariant::Nmos6502Undocumented, CpuVariant::Cmos65C02] {
+        for opcode in 0..=0xFF {
+            let Some(instruction) = variant.instruction(opcode) else {
+                continue;
+            };
+            if !falls_through(instruction.opname) {
+                continue;
+            }
+            let mut cpu = cpu_with_program(variant, &[opcode, 0x00, 0x00]);
+            cpu.execute_instruction().unwrap();
+            assert_eq!(
+                cpu.pc,
+                START + instruction.bytes as u16,
+                "{:?} {:?} (${:02X}) on {:?}",
+                instruction.opname,
+                instruction.mode,
+                opcode,
+                variant
+            );
+        }
+    }
+}
+
+#[test]
+fn every_mode_is_covered() {
+    let modes: Vec<AddressingMode> = [CpuVariant::Nmos6502Undocumented, CpuVariant::Cmos65C02]
+        .iter()
+        .flat_map(|variant| (0..=0xFF).filter_map(|opcode| variant.instruction(opcode)))
+        .filter(|instruction| falls_through(instruction.opname))
+        .map(|instruction| instruction.mode)
+        .collect();
+    for mode in [
+        AddressingMode::Implied,
+        AddressingMode::Immediate,
+        AddressingMode::ZeroPage,
+        AddressingMode::ZeroPageX,
+        AddressingMode::ZeroPageY,
+        AddressingMode::Absolute,
+        AddressingMode::AbsoluteX,
+        AddressingMode::AbsoluteY,
+        AddressingMode::IndirectX,
+        AddressingMode::IndirectY,
+        AddressingMode::ZeroPageIndirect,
+        AddressingMode::ZeroPageRelative,
+    ] {
+        assert!(modes.contains(&mode), "{:?} is not exercised", mode);
+    }
+}
+
+#[test]
+fn immediate_reads_the_operand_byte() {
+    let mut cpu = cpu_with("LDA #$42");
+    run(&mut cpu, 1);
+    assert_eq!(cpu.a, 0x42);
+}
+
+#[test]
+fn zero_page_reads_page_zero() {
+    let mut cpu = cpu_with("LDA $80");
+    cpu.memory.write(0x0080, 0x42);
+    run(&mut cpu, 1);
+    assert_eq!(cpu.a, 0x42);
+}
+
+#[test]
+fn absolute_reads_the_last_byte_of_a_page() {
+    let mut cpu = cpu_with("LDA $12FF");
+    cpu.memory.write(0x12FF, 0x42);
+    cpu.memory.write(0x1200, 0x55);
+    run(&mut cpu, 1);
+    assert_eq!(cpu.a, 0x42);
+}
+
+#[test]
+fn absolute_x_carries_into_the_high_byte() {
+    let mut cpu = cpu_with("LDX #$01\nLDA $12FF,X\nSTA $12FF,X");
+    cpu.memory.write(0x1300, 0x42);
+    cpu.memory.write(0x1200, 0x55);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.a, 0x42);
+
+    cpu.a = 0x77;
+    run(&mut cpu, 1);
+    assert_eq!(cpu.memory.read(0x1300), 0x77);
+    assert_eq!(cpu.memory.read(0x1200), 0x55);
+}
+
+#[test]
+fn absolute_y_carries_into_the_high_byte() {
+    let mut cpu = cpu_with("LDY #$02\nLDA $12FF,Y\nSTA $12FF,Y");
+    cpu.memory.write(0x1301, 0x42);
+    cpu.memory.write(0x1201, 0x55);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.a, 0x42);
+
+    cpu.a = 0x77;
+    run(&mut cpu, 1);
+    assert_eq!(cpu.memory.read(0x1301), 0x77);
+    assert_eq!(cpu.memory.read(0x1201), 0x55);
+}
+
+#[test]
+fn absolute_indexed_wraps_past_the_top_of_memory() {
+    let mut cpu = cpu_with("LDX #$02\nLDA $FFFF,X");
+    cpu.memory.write(0x0001, 0x42);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.a, 0x42);
+}
+
+#[test]
+fn indexed_indirect_follows_a_pointer_to_a_page_end() {
+    let mut cpu = cpu_with("LDX #$04\nLDA ($20,X)");
+    cpu.memory.write_u16(0x0024, 0x12FF);
+    cpu.memory.write(0x12FF, 0x42);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.a, 0x42);
+}
+
+#[test]
+fn indirect_indexed_store_carries_into_the_high_byte() {
+    let mut cpu = cpu_with("LDY #$01\nLDA #$42\nSTA ($10),Y");
+    cpu.memory.write_u16(0x0010, 0x12FF);
+    run(&mut cpu, 3);
+    assert_eq!(cpu.memory.read(0x1300), 0x42);
+    assert_eq!(cpu.memory.read(0x1200), 0x00);
+}
+
+#[test]
+fn relative_branches_cross_pages_both_ways() {
+    // Offsets count from the instruction after the branch, at $0604
+    let mut cpu = cpu_with("LDX #$01\nBNE #$80");
+    run(&mut cpu, 2);
+    assert_eq!(cpu.pc, 0x0584);
+
+    let mut cpu = cpu_with("LDX #$01\nBNE #$7F");
+    run(&mut cpu, 2);
+    assert_eq!(cpu.pc, 0x0683);
+}
+
+#[test]
+fn zero_page_indirect_pointer_wraps() {
+    let mut cpu = cpu_for(CpuVariant::Cmos65C02, "LDA ($FF)");
+    cpu.memory.write(0x00FF, 0x34);
+    cpu.memory.write(0x0000, 0x12);
+    cpu.memory.write(0x0100, 0x56);
+    cpu.memory.write(0x1234, 0x42);
+    run(&mut cpu, 1);
+    assert_eq!(cpu.a, 0x42);
+}
+
+#[test]
+fn absolute_indexed_indirect_pointer_crosses_a_page() {
+    let mut cpu = cpu_for(CpuVariant::Cmos65C02, "LDX #$01\nJMP ($12FF,X)");
+    cpu.memory.write_u16(0x1300, 0x4321);
+    cpu.memory.write_u16(0x1200, 0x5555);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.pc, 0x4321);
+}
+
+#[test]
+fn zero_page_relative_tests_then_branches() {
+    let mut cpu = cpu_for(CpuVariant::Cmos65C02, "BBS0 $10,$10");
+    cpu.memory.write(0x0010, 0x01);
+    run(&mut cpu, 1);
+    assert_eq!(cpu.pc, 0x0613);
+}
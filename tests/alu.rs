@@ -0,0 +1,170 @@
+// ADC and SBC checked against an independent model for every combination of
+// A, operand, carry and decimal flag. The decimal model follows Bruce Clark's
+// description of the NMOS and 65C02 behaviour, invalid BCD included.
+
+mod common;
+
+use common::{START, cpu_with_program};
+use rs6502::cpu::{CPU, StatusFlags};
+use rs6502::variant::CpuVariant;
+
+const ADC_IMMEDIATE: u8 = 0x69;
+const SBC_IMMEDIATE: u8 = 0xE9;
+
+// A and the N, V, Z and C flags after an operation
+#[derive(Debug, PartialEq)]
+struct Outcome {
+    a: u8,
+    n: bool,
+    v: bool,
+    z: bool,
+    c: bool,
+}
+
+fn binary(a: u8, operand: u8, carry: bool) -> Outcome {
+    let sum = a as u16 + operand as u16 + carry as u16;
+    let result = sum as u8;
+    Outcome {
+        a: result,
+        n: result & 0x80 != 0,
+        v: (a ^ result) & (operand ^ result) & 0x80 != 0,
+        z: result == 0,
+        c: sum > 0xFF,
+    }
+}
+
+fn binary_sbc(a: u8, operand: u8, carry: bool) -> Outcome {
+    binary(a, !operand, carry)
+}
+
+// Clark's sequences 1 and 2: the low digit is corrected first, N and V are
+// read from the signed sum before the high digit is
+fn decimal_adc(a: u8, operand: u8, carry: bool, cmos: bool) -> Outcome {
+    let mut low = (a & 0x0F) as i32 + (operand & 0x0F) as i32 + carry as i32;
+    if low >= 0x0A {
+        low = ((low + 0x06) & 0x0F) + 0x10;
+    }
+    let signed = (a & 0xF0) as i8 as i32 + (operand & 0xF0) as i8 as i32 + low;
+    let mut sum = (a & 0xF0) as i32 + (operand & 0xF0) as i32 + low;
+    let n = sum & 0x80 != 0;
+    if sum >= 0xA0 {
+        sum += 0x60;
+    }
+    let result = sum as u8;
+    Outcome {
+        a: result,
+        n: if cmos { result & 0x80 != 0 } else { n },
+        v: !(-128..=127).contains(&signed),
+        z: if cmos {
+            result == 0
+        } else {
+            binary(a, operand, carry).z
+        },
+        c: sum >= 0x100,
+    }
+}
+
+// Clark's sequence 3 for the NMOS part and 4 for the 65C02. The flags are
+// those of the binary subtraction, except N and Z on the 65C02.
+fn decimal_sbc(a: u8, operand: u8, carry: bool, cmos: bool) -> Outcome {
+    let borrow = !carry as i32;
+    let low = (a & 0x0F) as i32 - (operand & 0x0F) as i32 - borrow;
+    let result = if cmos {
+        let mut difference = a as i32 - operand as i32 - borrow;
+        if difference < 0 {
+            difference -= 0x60;
+        }
+        if low < 0 {
+            difference -= 0x06;
+        }
+        difference as u8
+    } else {
+        let low = if low < 0 {
+            ((low - 0x06) & 0x0F) - 0x10
+        } else {
+            low
+        };
+        let mut difference = (a & 0xF0) as i32 - (operand & 0xF0) as i32 + low;
+        if difference < 0 {
+            difference -= 0x60;
+        }
+        difference as u8
+    };
+    let flags = binary_sbc(a, operand, carry);
+    Outcome {
+        a: result,
+        n: if cmos { result & 0x80 != 0 } else { flags.n },
+        z: if cmos { result == 0 } else { flags.z },
+        ..flags
+    }
+}
+
+fn expected(
+    variant: CpuVariant,
+    opcode: u8,
+    a: u8,
+    operand: u8,
+    carry: bool,
+    decimal: bool,
+) -> Outcome {
+    let cmos = variant.is_cmos();
+    match (opcode, decimal && variant.has_decimal_mode()) {
+        (ADC_IMMEDIATE, false) => binary(a, operand, carry),
+        (ADC_IMMEDIATE, true) => decimal_adc(a, operand, carry, cmos),
+        (_, false) => binary_sbc(a, operand, carry),
+        (_, true) => decimal_sbc(a, operand, carry, cmos),
+    }
+}
+
+fn execute(cpu: &mut CPU, opcode: u8, a: u8, operand: u8, carry: bool, decimal: bool) -> Outcome {
+    cpu.memory.write(START, opcode);
+    cpu.memory.write(START + 1, operand);
+    cpu.pc = START;
+    cpu.a = a;
+    cpu.status.set(StatusFlags::CARRY, carry);
+    cpu.status.set(StatusFlags::DECIMAL, decimal);
+    cpu.execute_instruction().unwrap();
+    Outcome {
+        a: cpu.a,
+        n: cpu.status.contains(StatusFlags::NEGATIVE),
+        v: cpu.status.contains(StatusFlags::OVERFLOW),
+        z: cpu.status.contains(StatusFlags::ZERO),
+        c: cpu.status.contains(StatusFlags::CARRY),
+    }
+}
+
+fn check_every_combination(variant: CpuVariant) {
+    let mut cpu = cpu_with_program(variant, &[]);
+    for opcode in [ADC_IMMEDIATE, SBC_IMMEDIATE] {
+        for decimal in [false, true] {
+            for carry in [false, true] {
+                for a in 0..=0xFF {
+                    for operand in 0..=0xFF {
+                        let model = expected(variant, opcode, a, operand, carry, decimal);
+                        let actual = execute(&mut cpu, opcode, a, operand, carry, decimal);
+                        assert_eq!(
+                            actual, model,
+                            "${:02X} with A=${:02X} M=${:02X} C={} D={} on {:?}",
+                            opcode, a, operand, carry, decimal, variant
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn nmos_matches_the_model() {
+    check_every_combination(CpuVariant::Nmos6502);
+}
+
+#[test]
+fn cmos_matches_the_model() {
+    check_every_combination(CpuVariant::Cmos65C02);
+}
+
+#[test]
+fn ricoh_2a03_matches_the_model() {
+    check_every_combination(CpuVariant::Ricoh2A03);
+}
@@ -0,0 +1,69 @@
+// Running the CPU against a bus other than plain Memory.
+
+mod common;
+
+use common::memory_with;
+use rs6502::assembler::assemble;
+use rs6502::bus::Bus;
+use rs6502::cpu::CPU;
+use rs6502::memory::Memory;
+
+// RAM with a counter register at $D000 that advances with the clock, and a
+// log of every write
+struct TimerBus {
+    ram: Memory,
+    ticks: u64,
+    writes: Vec<(u16, u8)>,
+}
+
+impl Bus for TimerBus {
+    fn read(&mut self, address: u16) -> u8 {
+        match address {
+            0xD000 => self.ticks as u8,
+            _ => self.ram.read(address),
+        }
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        self.writes.push((address, value));
+        self.ram.write(address, value);
+    }
+
+    fn tick(&mut self, cycles: u8) {
+        self.ticks += cycles as u64;
+    }
+}
+
+fn cpu_with(source: &str) -> CPU<TimerBus> {
+    let bus = TimerBus {
+        ram: memory_with(&assemble(source)),
+        ticks: 0,
+        writes: Vec::new(),
+    };
+    let mut cpu = CPU::new(bus);
+    cpu.reset();
+    cpu
+}
+
+#[test]
+fn bus_sees_reads_and_writes() {
+    let mut cpu = cpu_with("LDA $D000\nSTA $0200");
+    cpu.run_for_instructions(2);
+    // Reset took 7 cycles before LDA read the counter
+    assert_eq!(cpu.a, 7);
+    assert_eq!(cpu.memory.writes, [(0x0200, 7)]);
+}
+
+#[test]
+fn bus_ticks_with_the_cpu() {
+    let mut cpu = cpu_with("LDX #$05\nDEX\nBNE #$FD\nBRK");
+    cpu.run_for_instructions(11);
+    assert_eq!(cpu.memory.ticks, cpu.cycles);
+
+    let mut cpu = cpu_with("LDX #$05\nDEX\nBNE #$FD\nBRK");
+    for _ in 0..26 {
+        cpu.tick();
+    }
+    assert_eq!(cpu.memory.ticks, cpu.cycles);
+    assert_eq!(cpu.pc, 0x0605);
+}
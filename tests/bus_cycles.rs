@@ -0,0 +1,212 @@
+// The bus access `tick` makes on each cycle: dummy reads, the read-modify-write
+// double write (a double read on the 65C02), the read from the unfixed
+// address when indexing crosses a page, and the extra cycles of branches.
+
+mod common;
+
+use common::{cpu_for, cpu_with};
+use rs6502::cpu::{BusAccess, BusCycle, CPU};
+use rs6502::variant::CpuVariant;
+
+use BusAccess::{Read, Write};
+
+// Runs the setup instructions whole, then ticks through the next one
+fn trace(cpu: &mut CPU, setup: usize, cycles: usize) -> Vec<(BusAccess, u16)> {
+    for _ in 0..setup {
+        cpu.execute_instruction().unwrap();
+    }
+    (0..cycles)
+        .map(|_| cpu.tick().unwrap())
+        .map(|bus| (bus.access, bus.address))
+        .collect()
+}
+
+#[test]
+fn implied_instructions_read_the_next_byte() {
+    let mut cpu = cpu_with("NOP\nINX");
+    assert_eq!(trace(&mut cpu, 0, 2), [(Read, 0x0600), (Read, 0x0601)]);
+    assert_eq!(cpu.pc, 0x0601);
+}
+
+#[test]
+fn zero_page_indexed_reads_the_unindexed_address() {
+    let mut cpu = cpu_with("LDX #$05\nLDA $10,X");
+    assert_eq!(
+        trace(&mut cpu, 1, 4),
+        [
+            (Read, 0x0602),
+            (Read, 0x0603),
+            (Read, 0x0010),
+            (Read, 0x0015)
+        ]
+    );
+}
+
+#[test]
+fn pushes_and_pulls_touch_the_stack() {
+    let mut cpu = cpu_with("LDA #$42\nPHA\nPLA");
+    assert_eq!(
+        trace(&mut cpu, 1, 3),
+        [(Read, 0x0602), (Read, 0x0603), (Write, 0x01FD)]
+    );
+    assert_eq!(
+        trace(&mut cpu, 0, 4),
+        [
+            (Read, 0x0603),
+            (Read, 0x0604),
+            (Read, 0x01FC),
+            (Read, 0x01FD)
+        ]
+    );
+    assert_eq!(cpu.a, 0x42);
+}
+
+#[test]
+fn rts_reads_the_stack_then_the_return_address() {
+    let mut cpu = cpu_with("JSR $0610");
+    cpu.memory.write(0x0610, 0x60);
+    assert_eq!(
+        trace(&mut cpu, 1, 6),
+        [
+            (Read, 0x0610),
+            (Read, 0x0611),
+            (Read, 0x01FB),
+            (Read, 0x01FC),
+            (Read, 0x01FD),
+            (Read, 0x0602),
+        ]
+    );
+    assert_eq!(cpu.pc, 0x0603);
+}
+
+#[test]
+fn nmos_read_modify_write_writes_twice() {
+    let mut cpu = cpu_with("INC $10");
+    cpu.memory.write(0x0010, 0x41);
+    let cycles: Vec<BusCycle> = (0..5).map(|_| cpu.tick().unwrap()).collect();
+    let writes: Vec<(u16, u8)> = cycles
+        .iter()
+        .filter(|bus| bus.access == Write)
+        .map(|bus| (bus.address, bus.value))
+        .collect();
+    // The unmodified value goes back first
+    assert_eq!(writes, [(0x0010, 0x41), (0x0010, 0x42)]);
+    assert_eq!(cycles[2].address, 0x0010);
+    assert_eq!(cycles[2].access, Read);
+}
+
+#[test]
+fn cmos_read_modify_write_reads_twice() {
+    let mut cpu = cpu_for(CpuVariant::Cmos65C02, "INC $10");
+    cpu.memory.write(0x0010, 0x41);
+    assert_eq!(
+        trace(&mut cpu, 0, 5),
+        [
+            (Read, 0x0600),
+            (Read, 0x0601),
+            (Read, 0x0010),
+            (Read, 0x0010),
+            (Write, 0x0010),
+        ]
+    );
+    assert_eq!(cpu.memory.read(0x0010), 0x42);
+}
+
+#[test]
+fn indexed_read_across_a_page_reads_the_unfixed_address() {
+    let mut cpu = cpu_with("LDX #$01\nLDA $12FF,X");
+    assert_eq!(
+        trace(&mut cpu, 1, 5),
+        [
+            (Read, 0x0602),
+            (Read, 0x0603),
+            (Read, 0x0604),
+            (Read, 0x1200),
+            (Read, 0x1300),
+        ]
+    );
+
+    // No fix-up when the page is not crossed
+    let mut cpu = cpu_with("LDX #$01\nLDA $12FE,X");
+    let cycles = trace(&mut cpu, 1, 4);
+    assert_eq!(cycles[3], (Read, 0x12FF));
+}
+
+#[test]
+fn cmos_fix_up_rereads_the_operand() {
+    let mut cpu = cpu_for(CpuVariant::Cmos65C02, "LDX #$01\nLDA $12FF,X");
+    let cycles = trace(&mut cpu, 1, 5);
+    assert_eq!(cycles[3..], [(Read, 0x0604), (Read, 0x1300)]);
+}
+
+#[test]
+fn indexed_write_always_reads_first() {
+    let mut cpu = cpu_with("LDX #$01\nSTA $12FE,X");
+    let cycles = trace(&mut cpu, 1, 5);
+    assert_eq!(cycles[3..], [(Read, 0x12FF), (Write, 0x12FF)]);
+}
+
+#[test]
+fn branch_not_taken_takes_two_cycles() {
+    let mut cpu = cpu_with("LDX #$00\nBNE #$10\nNOP");
+    assert_eq!(trace(&mut cpu, 1, 2), [(Read, 0x0602), (Read, 0x0603)]);
+    assert_eq!(cpu.tick().unwrap().address, 0x0604);
+}
+
+#[test]
+fn branch_taken_fetches_the_next_opcode_and_discards_it() {
+    let mut cpu = cpu_with("LDX #$01\nBNE #$10");
+    assert_eq!(
+        trace(&mut cpu, 1, 3),
+        [(Read, 0x0602), (Read, 0x0603), (Read, 0x0604)]
+    );
+    assert_eq!(cpu.tick().unwrap().address, 0x0614);
+}
+
+#[test]
+fn branch_across_a_page_reads_the_wrong_page_first() {
+    // $0604 - $10 is $05F4; PCL is updated a cycle before PCH
+    let mut cpu = cpu_with("LDX #$01\nBNE #$F0");
+    assert_eq!(
+        trace(&mut cpu, 1, 4),
+        [
+            (Read, 0x0602),
+            (Read, 0x0603),
+            (Read, 0x0604),
+            (Read, 0x06F4)
+        ]
+    );
+    assert_eq!(cpu.pc, 0x05F4);
+    assert_eq!(cpu.tick().unwrap().address, 0x05F4);
+}
+
+#[test]
+fn interrupt_sequence_pushes_then_reads_the_vector() {
+    let mut cpu = cpu_with("CLI\nNOP");
+    cpu.memory.write_u16(0xFFFE, 0x0700);
+    cpu.execute_instruction().unwrap();
+    cpu.set_irq_line(true);
+    assert_eq!(
+        trace(&mut cpu, 0, 7),
+        [
+            (Read, 0x0601),
+            (Read, 0x0601),
+            (Write, 0x01FD),
+            (Write, 0x01FC),
+            (Write, 0x01FB),
+            (Read, 0xFFFE),
+            (Read, 0xFFFF),
+        ]
+    );
+    assert_eq!(cpu.pc, 0x0700);
+}
+
+#[test]
+fn w65c816_runs_each_instruction_on_its_first_cycle() {
+    let mut cpu = cpu_for(CpuVariant::W65C816, "LDA $1234");
+    cpu.memory.write(0x1234, 0x42);
+    cpu.tick();
+    assert_eq!(cpu.a, 0x42);
+    // The rest of its 4 cycles idle on the bus at PC
+    assert_eq!(trace(&mut cpu, 0, 3), [(Read, 0x0603); 3]);
+}
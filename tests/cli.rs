@@ -0,0 +1,38 @@
+// The rs6502 binary end to end: it assembles a file, runs it and exits with
+// the final value of A.
+
+use std::env;
+use std::fs;
+use std::process::{self, Command, Output};
+
+// Assembles and runs `source` with `args` before the file name
+fn run(name: &str, source: &str, args: &[&str]) -> Output {
+    let path = env::temp_dir().join(format!("rs6502-{}-{}.asm", name, process::id()));
+    fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_rs6502"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    output
+}
+
+#[test]
+fn w65c816_banks_do_not_alias() {
+    let source = "LDA #$42\nSTA $123456\nLDA #$07\nSTA $023456\nLDA $123456\nBRK";
+    let output = run("banks", source, &["--cpu", "65816"]);
+    assert_eq!(output.status.code(), Some(0x42));
+}
+
+#[test]
+fn wai_with_nothing_to_wake_it_exits() {
+    let output = run(
+        "wai",
+        "WAI\nBRK",
+        &["--cpu", "65c02", "--max-cycles", "100"],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("waiting for an interrupt"), "{}", stderr);
+}
@@ -0,0 +1,228 @@
+// The instructions and addressing mode the 65C02 adds to the NMOS set, and
+// the WDC additions: bit instructions, WAI and STP.
+
+mod common;
+
+use common::cpu_for;
+use rs6502::cpu::{CPU, CpuError, HaltReason, StatusFlags, StepOutcome};
+use rs6502::variant::CpuVariant;
+
+fn cmos(source: &str) -> CPU {
+    cpu_for(CpuVariant::Cmos65C02, source)
+}
+
+fn run(cpu: &mut CPU, instructions: usize) {
+    for _ in 0..instructions {
+        cpu.execute_instruction().unwrap();
+    }
+}
+
+fn step(cpu: &mut CPU) -> StepOutcome {
+    cpu.execute_instruction().unwrap()
+}
+
+fn flag(cpu: &CPU, flag: StatusFlags) -> bool {
+    cpu.status.contains(flag)
+}
+
+#[test]
+fn bra_always_branches() {
+    let mut cpu = cmos("BRA #$10");
+    run(&mut cpu, 1);
+    assert_eq!(cpu.pc, 0x0612);
+
+    let mut cpu = cmos("BRA #$FE");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.pc, 0x0600);
+}
+
+#[test]
+fn stz_stores_zero_in_every_mode() {
+    let mut cpu = cmos("LDX #$01\nSTZ $10\nSTZ $10,X\nSTZ $1234\nSTZ $1234,X");
+    for address in [0x0010, 0x0011, 0x1234, 0x1235] {
+        cpu.memory.write(address, 0xFF);
+    }
+    run(&mut cpu, 5);
+    for address in [0x0010, 0x0011, 0x1234, 0x1235] {
+        assert_eq!(cpu.memory.read(address), 0x00, "${:04X}", address);
+    }
+}
+
+#[test]
+fn tsb_sets_the_bits_of_a_in_memory() {
+    let mut cpu = cmos("LDA #$0F\nTSB $10");
+    cpu.memory.write(0x0010, 0x30);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.memory.read(0x0010), 0x3F);
+    assert_eq!(cpu.a, 0x0F);
+    // Z tests A & M before the write
+    assert!(flag(&cpu, StatusFlags::ZERO));
+}
+
+#[test]
+fn trb_clears_the_bits_of_a_in_memory() {
+    let mut cpu = cmos("LDA #$0F\nTRB $1234");
+    cpu.memory.write(0x1234, 0x3C);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.memory.read(0x1234), 0x30);
+    assert!(!flag(&cpu, StatusFlags::ZERO));
+}
+
+#[test]
+fn index_registers_push_and_pull() {
+    let mut cpu = cmos("LDX #$42\nPHX\nPLY");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.y, 0x42);
+    assert_eq!(cpu.sp, 0xFD);
+
+    let mut cpu = cmos("LDY #$80\nLDX #$00\nPHY\nPLX");
+    run(&mut cpu, 4);
+    assert_eq!(cpu.x, 0x80);
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+    assert!(!flag(&cpu, StatusFlags::ZERO));
+}
+
+#[test]
+fn inc_and_dec_work_on_a() {
+    let mut cpu = cmos("LDA #$FF\nINC");
+    run(&mut cpu, 2);
+    assert_eq!(cpu.a, 0x00);
+    assert!(flag(&cpu, StatusFlags::ZERO));
+
+    let mut cpu = cmos("LDA #$00\nDEC");
+    run(&mut cpu, 2);
+    assert_eq!(cpu.a, 0xFF);
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+}
+
+#[test]
+fn zero_page_indirect_is_unindexed() {
+    let mut cpu = cmos("LDX #$04\nLDY #$04\nLDA ($10)\nSTA ($12)");
+    cpu.memory.write_u16(0x0010, 0x1234);
+    cpu.memory.write_u16(0x0012, 0x2000);
+    cpu.memory.write(0x1234, 0x42);
+    run(&mut cpu, 4);
+    assert_eq!(cpu.a, 0x42);
+    assert_eq!(cpu.memory.read(0x2000), 0x42);
+}
+
+#[test]
+fn bit_immediate_only_sets_z() {
+    // Operand bits 7 and 6 are set, but N and V stay clear
+    let mut cpu = cmos("LDA #$01\nBIT #$C0");
+    run(&mut cpu, 2);
+    assert!(flag(&cpu, StatusFlags::ZERO));
+    assert!(!flag(&cpu, StatusFlags::NEGATIVE));
+    assert!(!flag(&cpu, StatusFlags::OVERFLOW));
+
+    // And set ones stay set
+    let mut cpu = cmos("LDA #$01\nBIT $10\nBIT #$01");
+    cpu.memory.write(0x0010, 0xC0);
+    run(&mut cpu, 3);
+    assert!(!flag(&cpu, StatusFlags::ZERO));
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+    assert!(flag(&cpu, StatusFlags::OVERFLOW));
+}
+
+#[test]
+fn rmb_and_smb_change_one_bit() {
+    let mut cpu = cmos("RMB3 $10\nSMB0 $11\nRMB7 $12\nSMB7 $13");
+    cpu.memory.write(0x0010, 0xFF);
+    cpu.memory.write(0x0012, 0x7F);
+    run(&mut cpu, 4);
+    assert_eq!(cpu.memory.read(0x0010), 0xF7);
+    assert_eq!(cpu.memory.read(0x0011), 0x01);
+    // Clearing a clear bit changes nothing
+    assert_eq!(cpu.memory.read(0x0012), 0x7F);
+    assert_eq!(cpu.memory.read(0x0013), 0x80);
+}
+
+#[test]
+fn bbr_and_bbs_branch_on_a_zero_page_bit() {
+    let mut cpu = cmos("BBS0 $10,$10");
+    cpu.memory.write(0x0010, 0x01);
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(6));
+    assert_eq!(cpu.pc, 0x0613);
+
+    let mut cpu = cmos("BBS0 $10,$10");
+    cpu.memory.write(0x0010, 0xFE);
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(5));
+    assert_eq!(cpu.pc, 0x0603);
+
+    let mut cpu = cmos("BBR7 $10,$10");
+    cpu.memory.write(0x0010, 0x7F);
+    step(&mut cpu);
+    assert_eq!(cpu.pc, 0x0613);
+
+    let mut cpu = cmos("BBR7 $10,$10");
+    cpu.memory.write(0x0010, 0x80);
+    step(&mut cpu);
+    assert_eq!(cpu.pc, 0x0603);
+}
+
+#[test]
+fn bbs_across_a_page_pays_another_cycle() {
+    // Back from $0603 to page 5
+    let mut cpu = cmos("BBS1 $10,$F0");
+    cpu.memory.write(0x0010, 0x02);
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(7));
+    assert_eq!(cpu.pc, 0x05F3);
+
+    // BBS7 forward from $06F3 to page 7
+    let mut cpu = cmos("");
+    cpu.memory.load_program(vec![0xFF, 0x10, 0x20], 0x06F0);
+    cpu.memory.write(0x0010, 0x80);
+    cpu.pc = 0x06F0;
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(7));
+    assert_eq!(cpu.pc, 0x0713);
+}
+
+#[test]
+fn wai_services_an_irq_when_i_is_clear() {
+    let mut cpu = cmos("CLI\nWAI\nNOP");
+    cpu.memory.write_u16(0xFFFE, 0x0700);
+    run(&mut cpu, 2);
+    assert!(cpu.waiting);
+    assert_eq!(step(&mut cpu), StepOutcome::Waiting);
+    assert_eq!(step(&mut cpu), StepOutcome::Waiting);
+
+    cpu.irq();
+    assert_eq!(step(&mut cpu), StepOutcome::Interrupted(7));
+    assert!(!cpu.waiting);
+    assert_eq!(cpu.pc, 0x0700);
+    // The handler returns to the instruction after WAI
+    assert_eq!(cpu.memory.read_u16(0x01FC), 0x0602);
+}
+
+#[test]
+fn wai_resumes_without_servicing_a_masked_irq() {
+    let mut cpu = cmos("SEI\nWAI\nINX");
+    cpu.memory.write_u16(0xFFFE, 0x0700);
+    run(&mut cpu, 2);
+    assert_eq!(step(&mut cpu), StepOutcome::Waiting);
+
+    cpu.set_irq_line(true);
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(2));
+    assert!(!cpu.waiting);
+    assert_eq!(cpu.x, 0x01);
+    assert_eq!(cpu.pc, 0x0603);
+}
+
+#[test]
+fn stp_halts_until_reset() {
+    let mut cpu = cmos("STP\nINX");
+    let stopped = Err(CpuError::Halted(HaltReason::Stopped));
+    assert_eq!(cpu.execute_instruction(), stopped);
+    assert_eq!(cpu.pc, 0x0601);
+
+    // Neither another step nor an interrupt wakes it
+    cpu.nmi();
+    assert_eq!(cpu.execute_instruction(), stopped);
+    assert_eq!(cpu.x, 0x00);
+
+    cpu.reset();
+    assert_eq!(cpu.pc, 0x0600);
+    cpu.pc = 0x0601;
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(2));
+    assert_eq!(cpu.x, 0x01);
+}
@@ -0,0 +1,36 @@
+// Fixtures shared by the integration tests. Each test binary uses only some
+// of them.
+#![allow(dead_code)]
+
+use rs6502::assembler::assemble_for;
+use rs6502::cpu::CPU;
+use rs6502::memory::Memory;
+use rs6502::variant::CpuVariant;
+
+/// Where test programs are loaded and the reset vector points.
+pub const START: u16 = 0x0600;
+
+/// Memory with `program` at `START` and the reset vector pointing at it.
+pub fn memory_with(program: &[u8]) -> Memory {
+    let mut memory = Memory::new();
+    memory.load_program(program.to_vec(), START);
+    memory.write_u16(0xFFFC, START);
+    memory
+}
+
+/// A CPU of `variant` reset into `program`.
+pub fn cpu_with_program(variant: CpuVariant, program: &[u8]) -> CPU {
+    let mut cpu = CPU::with_variant(memory_with(program), variant);
+    cpu.reset();
+    cpu
+}
+
+/// A CPU of `variant` reset into `source`, assembled for that variant.
+pub fn cpu_for(variant: CpuVariant, source: &str) -> CPU {
+    cpu_with_program(variant, &assemble_for(source, variant))
+}
+
+/// An NMOS 6502 reset into `source`.
+pub fn cpu_with(source: &str) -> CPU {
+    cpu_for(CpuVariant::Nmos6502, source)
+}
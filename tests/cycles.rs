@@ -0,0 +1,92 @@
+// Clock cycles per instruction, with the penalties for indexing across a
+// page, taken branches and 65C02 decimal arithmetic.
+
+mod common;
+
+use common::{cpu_for, cpu_with};
+use rs6502::cpu::{CPU, StepOutcome};
+use rs6502::variant::CpuVariant;
+
+// Runs the setup instructions, then returns the cycles of the next one
+fn cycles_after(cpu: &mut CPU, setup: usize) -> u8 {
+    for _ in 0..setup {
+        cpu.execute_instruction().unwrap();
+    }
+    match cpu.execute_instruction().unwrap() {
+        StepOutcome::Executed(cycles) => cycles,
+        outcome => panic!("expected an instruction, got {:?}", outcome),
+    }
+}
+
+#[test]
+fn base_counts_come_from_the_opcode_table() {
+    assert_eq!(cycles_after(&mut cpu_with("NOP"), 0), 2);
+    assert_eq!(cycles_after(&mut cpu_with("LDA $1234"), 0), 4);
+    assert_eq!(cycles_after(&mut cpu_with("INC $1234"), 0), 6);
+    assert_eq!(cycles_after(&mut cpu_with("JSR $1234"), 0), 6);
+}
+
+#[test]
+fn indexed_reads_pay_for_crossing_a_page() {
+    assert_eq!(cycles_after(&mut cpu_with("LDX #$01\nLDA $12FE,X"), 1), 4);
+    assert_eq!(cycles_after(&mut cpu_with("LDX #$01\nLDA $12FF,X"), 1), 5);
+    assert_eq!(cycles_after(&mut cpu_with("LDY #$01\nLDA $12FF,Y"), 1), 5);
+
+    let mut cpu = cpu_with("LDY #$01\nLDA ($10),Y");
+    cpu.memory.write_u16(0x0010, 0x12FE);
+    assert_eq!(cycles_after(&mut cpu, 1), 5);
+    let mut cpu = cpu_with("LDY #$01\nLDA ($10),Y");
+    cpu.memory.write_u16(0x0010, 0x12FF);
+    assert_eq!(cycles_after(&mut cpu, 1), 6);
+}
+
+#[test]
+fn indexed_writes_always_pay() {
+    assert_eq!(cycles_after(&mut cpu_with("LDX #$01\nSTA $12FE,X"), 1), 5);
+    assert_eq!(cycles_after(&mut cpu_with("LDX #$01\nSTA $12FF,X"), 1), 5);
+    assert_eq!(cycles_after(&mut cpu_with("LDX #$01\nINC $12FE,X"), 1), 7);
+    assert_eq!(cycles_after(&mut cpu_with("LDX #$01\nINC $12FF,X"), 1), 7);
+}
+
+#[test]
+fn cmos_shifts_only_pay_when_crossing() {
+    let cmos = CpuVariant::Cmos65C02;
+    assert_eq!(
+        cycles_after(&mut cpu_for(cmos, "LDX #$01\nASL $12FE,X"), 1),
+        6
+    );
+    assert_eq!(
+        cycles_after(&mut cpu_for(cmos, "LDX #$01\nASL $12FF,X"), 1),
+        7
+    );
+    assert_eq!(
+        cycles_after(&mut cpu_for(cmos, "LDX #$01\nINC $12FE,X"), 1),
+        7
+    );
+}
+
+#[test]
+fn branches_pay_when_taken_and_again_across_a_page() {
+    // Not taken
+    assert_eq!(cycles_after(&mut cpu_with("LDX #$00\nBNE #$10"), 1), 2);
+    // Taken within the page
+    assert_eq!(cycles_after(&mut cpu_with("LDX #$01\nBNE #$10"), 1), 3);
+    // Taken back to page 5
+    assert_eq!(cycles_after(&mut cpu_with("LDX #$01\nBNE #$F0"), 1), 4);
+    // Taken forward from $0684 to page 7
+    let mut cpu = cpu_with("");
+    cpu.memory
+        .load_program(vec![0xA2, 0x01, 0xD0, 0x7F], 0x0680);
+    cpu.pc = 0x0680;
+    assert_eq!(cycles_after(&mut cpu, 1), 4);
+}
+
+#[test]
+fn cmos_decimal_arithmetic_pays_a_cycle() {
+    let cmos = CpuVariant::Cmos65C02;
+    assert_eq!(cycles_after(&mut cpu_for(cmos, "ADC #$01"), 0), 2);
+    assert_eq!(cycles_after(&mut cpu_for(cmos, "SED\nADC #$01"), 1), 3);
+    assert_eq!(cycles_after(&mut cpu_for(cmos, "SED\nSBC $10"), 1), 4);
+    // The NMOS part corrects for free
+    assert_eq!(cycles_after(&mut cpu_with("SED\nADC #$01"), 1), 2);
+}
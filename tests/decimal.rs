@@ -0,0 +1,175 @@
+// NMOS decimal mode ADC and SBC. The accumulator gets the BCD result, but Z
+// comes from the binary sum and, for ADC, N and V from the sum before the
+// high digit is corrected; SBC sets every flag as in binary mode. Invalid
+// BCD operands are corrected digit by digit all the same. The 2A03 keeps the
+// D flag but always adds in binary.
+
+mod common;
+
+use common::{cpu_for, cpu_with};
+use rs6502::cpu::{CPU, StatusFlags};
+use rs6502::variant::CpuVariant;
+
+// Runs `instruction` with an immediate operand in decimal mode and returns
+// the CPU afterwards
+fn decimal(instruction: &str, a: u8, operand: u8, carry: bool) -> CPU {
+    let mut cpu = cpu_with(&format!("{} #${:02X}", instruction, operand));
+    cpu.a = a;
+    cpu.status.insert(StatusFlags::DECIMAL);
+    cpu.status.set(StatusFlags::CARRY, carry);
+    cpu.execute_instruction().unwrap();
+    cpu
+}
+
+// N, V, Z and C as `NV-BDIZC` prints them, the rest masked out
+fn nvzc(cpu: &CPU) -> String {
+    let flags = cpu.status.bits() & 0b1100_0011;
+    StatusFlags::from_bits(flags).to_string()
+}
+
+#[test]
+fn adc_adds_bcd_digits() {
+    let cpu = decimal("ADC", 0x12, 0x34, false);
+    assert_eq!(cpu.a, 0x46);
+    assert_eq!(nvzc(&cpu), "nv-bdizc");
+
+    // 58 + 46 + 1 = 105, but N and V see the uncorrected high digit of $A
+    let cpu = decimal("ADC", 0x58, 0x46, true);
+    assert_eq!(cpu.a, 0x05);
+    assert_eq!(nvzc(&cpu), "NV-bdizC");
+}
+
+#[test]
+fn adc_zero_flag_follows_the_binary_sum() {
+    // 99 + 1 is 00 carry 1, but $9A is not zero
+    let cpu = decimal("ADC", 0x99, 0x01, false);
+    assert_eq!(cpu.a, 0x00);
+    assert_eq!(nvzc(&cpu), "Nv-bdizC");
+
+    // 99 + 67 is 66 carry 1, but $99 + $67 is $100
+    let cpu = decimal("ADC", 0x99, 0x67, false);
+    assert_eq!(cpu.a, 0x66);
+    assert_eq!(nvzc(&cpu), "nv-bdiZC");
+}
+
+#[test]
+fn adc_negative_and_overflow_come_before_the_high_digit_fix() {
+    // 24 + 56 = 80: the high digit is 8 before any correction
+    let cpu = decimal("ADC", 0x24, 0x56, false);
+    assert_eq!(cpu.a, 0x80);
+    assert_eq!(nvzc(&cpu), "NV-bdizc");
+
+    // 93 + 82 = 175: the uncorrected high digit wraps past 9 and then $F
+    let cpu = decimal("ADC", 0x93, 0x82, false);
+    assert_eq!(cpu.a, 0x75);
+    assert_eq!(nvzc(&cpu), "nV-bdizC");
+
+    let cpu = decimal("ADC", 0x89, 0x76, false);
+    assert_eq!(cpu.a, 0x65);
+    assert_eq!(nvzc(&cpu), "nv-bdizC");
+}
+
+#[test]
+fn adc_corrects_invalid_bcd() {
+    let cpu = decimal("ADC", 0x0F, 0x01, false);
+    assert_eq!(cpu.a, 0x16);
+    assert_eq!(nvzc(&cpu), "nv-bdizc");
+
+    let cpu = decimal("ADC", 0x1F, 0x1F, false);
+    assert_eq!(cpu.a, 0x34);
+    assert_eq!(nvzc(&cpu), "nv-bdizc");
+
+    let cpu = decimal("ADC", 0x80, 0xF0, false);
+    assert_eq!(cpu.a, 0xD0);
+    assert_eq!(nvzc(&cpu), "nV-bdizC");
+
+    let cpu = decimal("ADC", 0xFF, 0xFF, true);
+    assert_eq!(cpu.a, 0x55);
+    assert_eq!(nvzc(&cpu), "Nv-bdizC");
+}
+
+#[test]
+fn sbc_subtracts_bcd_digits() {
+    let cpu = decimal("SBC", 0x46, 0x12, true);
+    assert_eq!(cpu.a, 0x34);
+    assert_eq!(nvzc(&cpu), "nv-bdizC");
+
+    // A borrow out of the low digit
+    let cpu = decimal("SBC", 0x40, 0x13, true);
+    assert_eq!(cpu.a, 0x27);
+    assert_eq!(nvzc(&cpu), "nv-bdizC");
+
+    // Carry clear borrows one more
+    let cpu = decimal("SBC", 0x32, 0x02, false);
+    assert_eq!(cpu.a, 0x29);
+    assert_eq!(nvzc(&cpu), "nv-bdizC");
+}
+
+#[test]
+fn sbc_flags_follow_the_binary_difference() {
+    // 00 - 01 is 99 with a borrow; $00 - $01 is $FF
+    let cpu = decimal("SBC", 0x00, 0x01, true);
+    assert_eq!(cpu.a, 0x99);
+    assert_eq!(nvzc(&cpu), "Nv-bdizc");
+
+    // $80 - $01 overflows as a signed subtraction
+    let cpu = decimal("SBC", 0x80, 0x01, true);
+    assert_eq!(cpu.a, 0x79);
+    assert_eq!(nvzc(&cpu), "nV-bdizC");
+
+    let cpu = decimal("SBC", 0x12, 0x12, true);
+    assert_eq!(cpu.a, 0x00);
+    assert_eq!(nvzc(&cpu), "nv-bdiZC");
+}
+
+#[test]
+fn sbc_corrects_invalid_bcd() {
+    // A low digit of $A with no borrow is left alone
+    let cpu = decimal("SBC", 0x0A, 0x00, true);
+    assert_eq!(cpu.a, 0x0A);
+    assert_eq!(nvzc(&cpu), "nv-bdizC");
+
+    let cpu = decimal("SBC", 0xFF, 0xFF, false);
+    assert_eq!(cpu.a, 0x99);
+    assert_eq!(nvzc(&cpu), "Nv-bdizc");
+}
+
+#[test]
+fn binary_mode_ignores_bcd() {
+    let mut cpu = cpu_with("ADC #$01");
+    cpu.a = 0x09;
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.a, 0x0A);
+}
+
+#[test]
+fn ricoh_2a03_ignores_sed() {
+    let mut cpu = cpu_for(
+        CpuVariant::Ricoh2A03,
+        "SED\nCLC\nLDA #$09\nADC #$01\nSEC\nSBC #$01\nSBC #$0A",
+    );
+    for _ in 0..4 {
+        cpu.execute_instruction().unwrap();
+    }
+    assert!(cpu.status.contains(StatusFlags::DECIMAL));
+    assert_eq!(cpu.a, 0x0A);
+    for _ in 0..3 {
+        cpu.execute_instruction().unwrap();
+    }
+    // $09 - $0A borrows to $FF rather than 99
+    assert_eq!(cpu.a, 0xFF);
+    assert!(!cpu.status.contains(StatusFlags::CARRY));
+}
+
+#[test]
+fn decimal_mode_follows_a_changed_variant() {
+    let mut cpu = cpu_with("SED\nADC #$01\nADC #$01");
+    cpu.a = 0x08;
+    cpu.execute_instruction().unwrap();
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.a, 0x09);
+
+    cpu.variant = CpuVariant::Ricoh2A03;
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.a, 0x0A);
+}
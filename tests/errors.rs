@@ -0,0 +1,74 @@
+// Faults and halts are reported through the Result of execute_instruction.
+
+mod common;
+
+use common::cpu_with_program;
+use rs6502::cpu::{CpuError, HaltReason, StepOutcome};
+use rs6502::variant::CpuVariant;
+
+#[test]
+fn illegal_opcode_halts_on_the_opcode() {
+    let mut cpu = cpu_with_program(CpuVariant::Nmos6502, &[0xEA, 0x02]);
+    assert_eq!(cpu.execute_instruction(), Ok(StepOutcome::Executed(2)));
+    let error = CpuError::IllegalOpcode {
+        pc: 0x0601,
+        opcode: 0x02,
+    };
+    assert_eq!(cpu.execute_instruction(), Err(error));
+    assert_eq!(cpu.pc, 0x0601);
+    assert_eq!(cpu.execute_instruction(), Err(error));
+    assert_eq!(error.to_string(), "illegal opcode $02 at $0601");
+}
+
+#[test]
+fn illegal_opcode_halts_when_ticked() {
+    let mut cpu = cpu_with_program(CpuVariant::Nmos6502, &[0x02]);
+    assert!(cpu.tick().is_some());
+    assert!(cpu.tick().is_none());
+    assert_eq!(
+        cpu.halt_reason(),
+        Some(HaltReason::IllegalOpcode {
+            pc: 0x0600,
+            opcode: 0x02
+        })
+    );
+}
+
+#[test]
+fn jam_is_reported() {
+    let mut cpu = cpu_with_program(CpuVariant::Nmos6502Undocumented, &[0x02]);
+    assert!(!cpu.is_halted());
+    let jam = Err(CpuError::Jammed);
+    assert_eq!(cpu.execute_instruction(), jam);
+    assert!(cpu.is_halted());
+    assert_eq!(cpu.halt_reason(), Some(HaltReason::Jam));
+
+    cpu.reset();
+    assert!(!cpu.is_halted());
+    assert_eq!(cpu.halt_reason(), None);
+}
+
+#[test]
+fn resume_leaves_a_jam_in_place() {
+    let mut cpu = cpu_with_program(CpuVariant::Nmos6502Undocumented, &[0x02, 0xE8]);
+    assert_eq!(cpu.execute_instruction(), Err(CpuError::Jammed));
+
+    cpu.resume();
+    assert_eq!(cpu.halt_reason(), Some(HaltReason::Jam));
+    assert_eq!(cpu.execute_instruction(), Err(CpuError::Jammed));
+    assert_eq!(cpu.x, 0x00);
+}
+
+#[test]
+fn halt_condition_is_reported() {
+    let mut cpu = cpu_with_program(CpuVariant::Nmos6502, &[0x00]);
+    cpu.halt_conditions.brk = true;
+    assert_eq!(
+        cpu.execute_instruction(),
+        Err(CpuError::Halted(HaltReason::Brk))
+    );
+
+    cpu.reset();
+    cpu.halt_conditions.brk = false;
+    assert_eq!(cpu.execute_instruction(), Ok(StepOutcome::Executed(7)));
+}
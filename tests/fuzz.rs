@@ -0,0 +1,86 @@
+// Runs random byte streams from random PCs to check that no combination of
+// opcodes, operands and addresses makes the emulator panic, including PC,
+// pointers and the stack wrapping around the ends of memory.
+
+use rs6502::cpu::{CPU, StatusFlags};
+use rs6502::memory::Memory;
+use rs6502::variant::CpuVariant;
+
+const VARIANTS: [CpuVariant; 5] = [
+    CpuVariant::Nmos6502,
+    CpuVariant::Nmos6502Undocumented,
+    CpuVariant::Cmos65C02,
+    CpuVariant::W65C816,
+    CpuVariant::Ricoh2A03,
+];
+
+// xorshift64, so the byte streams are reproducible without a dependency
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn byte(&mut self) -> u8 {
+        self.next() as u8
+    }
+}
+
+fn random_cpu(rng: &mut Rng, variant: CpuVariant) -> CPU {
+    let mut memory = Memory::new();
+    let program: Vec<u8> = (0..0x1_0000).map(|_| rng.byte()).collect();
+    memory.load_program(program, 0x0000);
+
+    let mut cpu = CPU::with_variant(memory, variant);
+    cpu.reset();
+    // Start near the top of memory as often as anywhere else
+    cpu.pc = match rng.byte() & 1 {
+        0 => 0xFFF0 | (rng.byte() & 0x0F) as u16,
+        _ => rng.next() as u16,
+    };
+    cpu.a = rng.byte();
+    cpu.x = rng.byte();
+    cpu.y = rng.byte();
+    cpu.sp = rng.byte();
+    cpu.status = StatusFlags::from_bits(rng.byte());
+    cpu
+}
+
+#[test]
+fn random_programs_execute_without_panicking() {
+    let mut rng = Rng(0x6502_6502_6502_6502);
+    for variant in VARIANTS {
+        for _ in 0..200 {
+            let mut cpu = random_cpu(&mut rng, variant);
+            for _ in 0..1000 {
+                if rng.byte() == 0 {
+                    cpu.set_nmi_line(true);
+                    cpu.set_nmi_line(false);
+                }
+                if cpu.execute_instruction().is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn random_programs_tick_without_panicking() {
+    let mut rng = Rng(0x2A03_65C0_2658_1600);
+    for variant in VARIANTS {
+        for _ in 0..50 {
+            let mut cpu = random_cpu(&mut rng, variant);
+            for _ in 0..4000 {
+                cpu.set_irq_line(rng.byte() < 4);
+                if cpu.tick().is_none() {
+                    break;
+                }
+            }
+        }
+    }
+}
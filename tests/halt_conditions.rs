@@ -0,0 +1,101 @@
+// Configurable stop conditions, checked through both execute_instruction and
+// tick.
+
+mod common;
+
+use common::cpu_with;
+use rs6502::cpu::{CPU, CpuError, HaltReason};
+
+// Runs until the CPU halts, giving up after `limit` instructions
+fn run(cpu: &mut CPU, limit: usize) -> Option<CpuError> {
+    (0..limit).find_map(|_| cpu.execute_instruction().err())
+}
+
+fn tick(cpu: &mut CPU, limit: usize) -> Option<HaltReason> {
+    (0..limit).find(|_| cpu.tick().is_none())?;
+    cpu.halt_reason()
+}
+
+#[test]
+fn breakpoint_stops_before_the_instruction() {
+    let mut cpu = cpu_with("LDA #$01\nLDA #$02\nLDA #$03");
+    cpu.halt_conditions.breakpoints.insert(0x0602);
+    let breakpoint = HaltReason::Breakpoint { pc: 0x0602 };
+    assert_eq!(run(&mut cpu, 10), Some(CpuError::Halted(breakpoint)));
+    assert_eq!(cpu.a, 0x01);
+
+    cpu.resume();
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.a, 0x02);
+}
+
+#[test]
+fn breakpoint_stops_ticking() {
+    let mut cpu = cpu_with("LDA #$01\nLDA #$02");
+    cpu.halt_conditions.breakpoints.insert(0x0602);
+    assert_eq!(
+        tick(&mut cpu, 10),
+        Some(HaltReason::Breakpoint { pc: 0x0602 })
+    );
+    // The reset sequence takes the first 7
+    assert_eq!(cpu.cycles, 9);
+}
+
+#[test]
+fn cycle_budget_stops_between_instructions() {
+    let mut cpu = cpu_with("LDA #$01\nLDA #$02\nLDA #$03");
+    cpu.halt_conditions.cycle_budget = Some(10);
+    assert_eq!(
+        run(&mut cpu, 10),
+        Some(CpuError::Halted(HaltReason::CycleBudget))
+    );
+    assert_eq!(cpu.cycles, 11);
+    assert_eq!(cpu.a, 0x02);
+}
+
+#[test]
+fn jmp_to_itself_is_a_self_loop() {
+    let mut cpu = cpu_with("NOP\nJMP $0601");
+    cpu.halt_conditions.self_loop = true;
+    let trap = HaltReason::SelfLoop { pc: 0x0601 };
+    assert_eq!(run(&mut cpu, 10), Some(CpuError::Halted(trap)));
+
+    let mut cpu = cpu_with("NOP\nJMP $0601");
+    cpu.halt_conditions.self_loop = true;
+    assert_eq!(tick(&mut cpu, 20), Some(trap));
+}
+
+#[test]
+fn branch_to_itself_is_a_self_loop() {
+    let mut cpu = cpu_with("LDX #$01\nBNE #$FE");
+    cpu.halt_conditions.self_loop = true;
+    let trap = HaltReason::SelfLoop { pc: 0x0602 };
+    assert_eq!(run(&mut cpu, 10), Some(CpuError::Halted(trap)));
+}
+
+#[test]
+fn backward_loop_is_not_a_self_loop() {
+    let mut cpu = cpu_with("LDX #$03\nDEX\nBNE #$FD\nBRK");
+    cpu.halt_conditions.self_loop = true;
+    cpu.halt_conditions.brk = true;
+    assert_eq!(run(&mut cpu, 20), Some(CpuError::Halted(HaltReason::Brk)));
+    assert_eq!(cpu.x, 0x00);
+}
+
+#[test]
+fn write_to_exit_address_halts() {
+    let source = "LDA #$2A\nSTA $F000\nLDA #$00";
+    let exit = HaltReason::ExitWrite {
+        address: 0xF000,
+        value: 0x2A,
+    };
+
+    let mut cpu = cpu_with(source);
+    cpu.halt_conditions.exit_address = Some(0xF000);
+    assert_eq!(run(&mut cpu, 10), Some(CpuError::Halted(exit)));
+    assert_eq!(cpu.a, 0x2A);
+
+    let mut cpu = cpu_with(source);
+    cpu.halt_conditions.exit_address = Some(0xF000);
+    assert_eq!(tick(&mut cpu, 20), Some(exit));
+}
@@ -0,0 +1,150 @@
+// Read, write and execute hooks on Memory.
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+mod common;
+
+use common::cpu_with;
+use rs6502::bus::Bus;
+use rs6502::memory::{Access, AccessKind, HookAction, Memory};
+
+// A hook that records what it sees, and the record it writes to
+fn recorder() -> (
+    Arc<Mutex<Vec<Access>>>,
+    impl FnMut(&Access) -> HookAction + Send,
+) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&log);
+    let hook = move |access: &Access| {
+        sink.lock().unwrap().push(*access);
+        HookAction::Continue
+    };
+    (log, hook)
+}
+
+#[test]
+fn hooks_see_address_value_and_pc() {
+    let mut cpu = cpu_with("LDA #$2A\nSTA $0200\nLDX $0200");
+    let (writes, hook) = recorder();
+    cpu.memory
+        .add_hook(AccessKind::Write, 0x0200..=0x02FF, hook);
+    let (reads, hook) = recorder();
+    cpu.memory.add_hook(AccessKind::Read, 0x0200..=0x0200, hook);
+    cpu.run_for_instructions(3);
+
+    let access = |address, value, pc, kind| Access {
+        address,
+        value,
+        pc,
+        kind,
+    };
+    assert_eq!(
+        *writes.lock().unwrap(),
+        [access(0x0200, 0x2A, 0x0602, AccessKind::Write)]
+    );
+    assert_eq!(
+        *reads.lock().unwrap(),
+        [access(0x0200, 0x2A, 0x0605, AccessKind::Read)]
+    );
+}
+
+#[test]
+fn execute_hooks_trace_instructions() {
+    let mut cpu = cpu_with("LDX #$02\nDEX\nBNE #$FD\nNOP");
+    let (fetches, hook) = recorder();
+    cpu.memory
+        .add_hook(AccessKind::Execute, 0x0000..=0xFFFF, hook);
+    cpu.run_for_instructions(6);
+    let pcs: Vec<u16> = fetches
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|access| access.pc)
+        .collect();
+    assert_eq!(pcs, [0x0600, 0x0602, 0x0603, 0x0602, 0x0603, 0x0605]);
+
+    // Ticking fetches the same way
+    let mut cpu = cpu_with("LDX #$02\nDEX");
+    let (fetches, hook) = recorder();
+    cpu.memory
+        .add_hook(AccessKind::Execute, 0x0000..=0xFFFF, hook);
+    for _ in 0..4 {
+        cpu.tick();
+    }
+    assert_eq!(fetches.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn hooks_replace_and_veto() {
+    let mut cpu = cpu_with("LDA $10\nSTA $20\nSTA $21");
+    cpu.memory.write(0x0010, 0x01);
+    cpu.memory.add_hook(AccessKind::Read, 0x0010..=0x0010, |_| {
+        HookAction::Replace(0x99)
+    });
+    cpu.memory
+        .add_hook(AccessKind::Write, 0x0021..=0x0021, |_| HookAction::Veto);
+    cpu.run_for_instructions(3);
+    assert_eq!(cpu.a, 0x99);
+    assert_eq!(cpu.memory.read(0x0010), 0x01);
+    assert_eq!(cpu.memory.read(0x0020), 0x99);
+    assert_eq!(cpu.memory.read(0x0021), 0x00);
+}
+
+#[test]
+fn later_hooks_see_earlier_replacements() {
+    let mut cpu = cpu_with("LDA #$01\nSTA $30");
+    cpu.memory
+        .add_hook(AccessKind::Write, 0x0030..=0x0030, |access| {
+            HookAction::Replace(access.value + 1)
+        });
+    cpu.memory
+        .add_hook(AccessKind::Write, 0x0030..=0x0030, |access| {
+            HookAction::Replace(access.value * 10)
+        });
+    cpu.run_for_instructions(2);
+    assert_eq!(cpu.memory.read(0x0030), 20);
+}
+
+#[test]
+fn removed_hooks_stop_firing() {
+    let mut cpu = cpu_with("STA $40\nSTA $40");
+    let (writes, hook) = recorder();
+    let id = cpu
+        .memory
+        .add_hook(AccessKind::Write, 0x0040..=0x0040, hook);
+    cpu.run_for_instructions(1);
+    assert!(cpu.memory.remove_hook(id));
+    assert!(!cpu.memory.remove_hook(id));
+    cpu.run_for_instructions(1);
+    assert_eq!(writes.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn direct_access_and_clones_skip_hooks() {
+    let mut memory = Memory::new();
+    let (log, hook) = recorder();
+    memory.add_hook(AccessKind::Write, 0x0000..=0xFFFF, hook);
+    memory.write(0x1234, 0x56);
+    assert!(log.lock().unwrap().is_empty());
+
+    let mut copy = memory.clone();
+    assert_eq!(copy, memory);
+    Bus::write(&mut copy, 0x1234, 0x78);
+    assert!(log.lock().unwrap().is_empty());
+
+    Bus::write(&mut memory, 0x1234, 0x78);
+    assert_eq!(log.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn cpu_with_hooks_moves_to_another_thread() {
+    let mut cpu = cpu_with("STA $0200");
+    let (writes, hook) = recorder();
+    cpu.memory
+        .add_hook(AccessKind::Write, 0x0200..=0x0200, hook);
+    thread::spawn(move || cpu.execute_instruction().unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(writes.lock().unwrap().len(), 1);
+}
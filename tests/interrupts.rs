@@ -0,0 +1,158 @@
+// IRQ, NMI and BRK: when interrupts are taken, what they push and how the
+// two input lines are triggered.
+
+mod common;
+
+use common::cpu_with;
+use rs6502::cpu::{CPU, StatusFlags, StepOutcome};
+
+const IRQ_HANDLER: u16 = 0x0700;
+const NMI_HANDLER: u16 = 0x0780;
+const RTI: u8 = 0x40;
+
+// Runs `source` with both handlers a bare RTI
+fn cpu_with_handlers(source: &str) -> CPU {
+    let mut cpu = cpu_with(source);
+    cpu.memory.write_u16(0xFFFE, IRQ_HANDLER);
+    cpu.memory.write_u16(0xFFFA, NMI_HANDLER);
+    cpu.memory.write(IRQ_HANDLER, RTI);
+    cpu.memory.write(NMI_HANDLER, RTI);
+    cpu
+}
+
+fn step(cpu: &mut CPU) -> StepOutcome {
+    cpu.execute_instruction().unwrap()
+}
+
+#[test]
+fn irq_is_taken_at_the_next_instruction() {
+    let mut cpu = cpu_with_handlers("CLI\nNOP\nNOP");
+    step(&mut cpu);
+    cpu.irq();
+    let cycles = cpu.cycles;
+    assert_eq!(step(&mut cpu), StepOutcome::Interrupted(7));
+    assert_eq!(cpu.cycles, cycles + 7);
+    assert_eq!(cpu.pc, IRQ_HANDLER);
+    assert_eq!(cpu.memory.read_u16(0x01FC), 0x0601);
+    assert!(cpu.status.contains(StatusFlags::INTERRUPT_DISABLE));
+
+    // Serviced, so only the return follows
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(6));
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(2));
+    assert_eq!(cpu.pc, 0x0602);
+}
+
+#[test]
+fn irq_request_waits_for_the_i_flag() {
+    let mut cpu = cpu_with_handlers("NOP\nCLI\nNOP");
+    cpu.irq();
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(2));
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(2));
+    assert_eq!(step(&mut cpu), StepOutcome::Interrupted(7));
+    assert_eq!(cpu.memory.read_u16(0x01FC), 0x0602);
+}
+
+#[test]
+fn nmi_is_taken_with_the_i_flag_set() {
+    let mut cpu = cpu_with_handlers("NOP");
+    cpu.nmi();
+    assert_eq!(step(&mut cpu), StepOutcome::Interrupted(7));
+    assert_eq!(cpu.pc, NMI_HANDLER);
+}
+
+#[test]
+fn nmi_takes_priority_over_irq() {
+    let mut cpu = cpu_with_handlers("CLI\nNOP");
+    step(&mut cpu);
+    cpu.irq();
+    cpu.nmi();
+    step(&mut cpu);
+    assert_eq!(cpu.pc, NMI_HANDLER);
+    // The IRQ is still pending once the NMI handler returns
+    step(&mut cpu);
+    step(&mut cpu);
+    assert_eq!(cpu.pc, IRQ_HANDLER);
+}
+
+#[test]
+fn requests_wait_for_a_ticked_instruction_to_finish() {
+    let mut cpu = cpu_with_handlers("LDA $1234\nNOP");
+    cpu.memory.write(0x1234, 0x42);
+    cpu.tick();
+    cpu.nmi();
+    for _ in 0..3 {
+        cpu.tick();
+    }
+    assert_eq!(cpu.a, 0x42);
+    assert_eq!(cpu.pc, 0x0603);
+
+    for _ in 0..7 {
+        cpu.tick();
+    }
+    assert_eq!(cpu.pc, NMI_HANDLER);
+    assert_eq!(cpu.memory.read_u16(0x01FC), 0x0603);
+}
+
+#[test]
+fn irq_line_is_level_triggered() {
+    let mut cpu = cpu_with_handlers("CLI\nNOP\nNOP");
+    step(&mut cpu);
+    cpu.set_irq_line(true);
+    assert_eq!(step(&mut cpu), StepOutcome::Interrupted(7));
+    // RTI clears I again while the line is still held
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(6));
+    assert_eq!(step(&mut cpu), StepOutcome::Interrupted(7));
+
+    cpu.set_irq_line(false);
+    step(&mut cpu);
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(2));
+    assert_eq!(cpu.pc, 0x0602);
+}
+
+#[test]
+fn irq_line_is_masked_by_the_i_flag() {
+    let mut cpu = cpu_with_handlers("NOP\nNOP");
+    cpu.set_irq_line(true);
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(2));
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(2));
+}
+
+#[test]
+fn nmi_line_is_edge_triggered() {
+    let mut cpu = cpu_with_handlers("NOP\nNOP\nNOP");
+    cpu.set_nmi_line(true);
+    assert_eq!(step(&mut cpu), StepOutcome::Interrupted(7));
+    step(&mut cpu);
+    // Still held, but there has been no new edge
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(2));
+
+    cpu.set_nmi_line(false);
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(2));
+    cpu.set_nmi_line(true);
+    assert_eq!(step(&mut cpu), StepOutcome::Interrupted(7));
+}
+
+#[test]
+fn brk_sets_b_on_the_pushed_copy_only() {
+    let mut cpu = cpu_with_handlers("BRK");
+    assert_eq!(step(&mut cpu), StepOutcome::Executed(7));
+    assert_eq!(cpu.pc, IRQ_HANDLER);
+    // BRK skips its padding byte
+    assert_eq!(cpu.memory.read_u16(0x01FC), 0x0602);
+    assert_eq!(cpu.memory.read(0x01FB), 0x34);
+    assert!(!cpu.status.contains(StatusFlags::BREAK));
+
+    // RTI drops it again
+    step(&mut cpu);
+    assert!(!cpu.status.contains(StatusFlags::BREAK));
+}
+
+#[test]
+fn irq_pushes_b_clear() {
+    let mut cpu = cpu_with_handlers("CLI\nNOP");
+    step(&mut cpu);
+    cpu.irq();
+    step(&mut cpu);
+    assert_eq!(cpu.memory.read(0x01FB), 0x20);
+    assert!(!cpu.status.contains(StatusFlags::BREAK));
+}
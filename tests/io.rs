@@ -0,0 +1,105 @@
+// Memory-mapped devices mounted on a MemoryMap: register reads and writes,
+// ticking and the interrupt output.
+
+use rs6502::assembler::assemble;
+use rs6502::cpu::CPU;
+use rs6502::io::IoDevice;
+use rs6502::memory_map::MemoryMap;
+
+// Counts down from a period written to register 1 and raises IRQ each time
+// it expires. Reading register 2 acknowledges the interrupt; register 0
+// reads the cycles left.
+struct Timer {
+    period: u32,
+    left: u32,
+    pending: bool,
+}
+
+impl IoDevice for Timer {
+    fn read_register(&mut self, offset: u16) -> u8 {
+        match offset {
+            0 => self.left as u8,
+            2 => {
+                let pending = self.pending;
+                self.pending = false;
+                pending as u8
+            }
+            _ => 0xFF,
+        }
+    }
+
+    fn write_register(&mut self, offset: u16, value: u8) {
+        if offset == 1 {
+            self.period = value as u32;
+            self.left = value as u32;
+        }
+    }
+
+    fn tick(&mut self, cycles: u8) {
+        if self.period == 0 {
+            return;
+        }
+        let mut cycles = cycles as u32;
+        while cycles >= self.left {
+            cycles -= self.left;
+            self.left = self.period;
+            self.pending = true;
+        }
+        self.left -= cycles;
+    }
+
+    fn irq(&self) -> bool {
+        self.pending
+    }
+}
+
+fn machine(source: &str, handler: &str) -> CPU<MemoryMap> {
+    let mut map = MemoryMap::new();
+    map.add_ram(0x0000..=0xFFFF);
+    map.add_device(
+        0xD000..=0xD003,
+        Timer {
+            period: 0,
+            left: 0,
+            pending: false,
+        },
+    );
+    map.load_program(assemble(source), 0x0600);
+    map.load_program(assemble(handler), 0x0700);
+    map.load_program(vec![0x00, 0x06], 0xFFFC);
+    map.load_program(vec![0x00, 0x07], 0xFFFE);
+    let mut cpu = CPU::new(map);
+    cpu.reset();
+    cpu
+}
+
+#[test]
+fn loads_and_stores_reach_registers() {
+    let mut cpu = machine("LDA #$40\nSTA $D001\nLDA $D003\nLDX $D000", "RTI");
+    cpu.run_for_instructions(4);
+    assert_eq!(cpu.a, 0xFF);
+    // Devices are ticked after each instruction, so the count has dropped by
+    // the cycles of STA and LDA
+    assert_eq!(cpu.x, 0x40 - 8);
+    assert_eq!(cpu.memory.memory().read(0xD001), 0x00);
+}
+
+#[test]
+fn device_interrupts_the_cpu() {
+    let mut cpu = machine(
+        "LDA #$32\nSTA $D001\nCLI\nJMP $0606",
+        "PHA\nLDA $D002\nINC $10\nPLA\nRTI",
+    );
+    cpu.run_for_cycles(1000);
+    // One interrupt per 50 cycles, each acknowledged by its handler
+    let interrupts = cpu.memory.memory().read(0x0010);
+    assert!((19..=20).contains(&interrupts), "{} interrupts", interrupts);
+}
+
+#[test]
+fn masked_device_interrupt_waits() {
+    let mut cpu = machine("LDA #$05\nSTA $D001\nNOP\nNOP\nNOP\nNOP", "INC $10\nRTI");
+    cpu.run_for_instructions(6);
+    assert_eq!(cpu.pc, 0x0609);
+    assert_eq!(cpu.memory.memory().read(0x0010), 0x00);
+}
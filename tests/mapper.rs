@@ -0,0 +1,140 @@
+// Bank switching through the reference mappers, and saving and restoring
+// their bank state.
+
+use rs6502::assembler::assemble;
+use rs6502::bus::Bus;
+use rs6502::cpu::CPU;
+use rs6502::mapper::{
+    BankState, BankStateError, BankedMemory, C64Banking, Mapper, SidewaysRom, Uxrom,
+};
+
+// PRG ROM whose banks are filled with their own bank number
+fn prg_rom(banks: usize) -> Vec<u8> {
+    (0..banks)
+        .flat_map(|bank| vec![bank as u8; 0x4000])
+        .collect()
+}
+
+#[test]
+fn uxrom_switches_the_low_window() {
+    let mut bus = BankedMemory::new(Uxrom::new(4), prg_rom(4));
+    assert_eq!(bus.read(0x8000), 0);
+    assert_eq!(bus.read(0xC000), 3);
+    bus.write(0xC123, 2);
+    assert_eq!(bus.read(0xBFFF), 2);
+    assert_eq!(bus.read(0xFFFF), 3);
+    // Bank numbers wrap to the size of the ROM
+    bus.write(0x8000, 5);
+    assert_eq!(bus.read(0x8000), 1);
+}
+
+#[test]
+fn program_switches_banks() {
+    let mut rom = prg_rom(4);
+    let code = assemble("LDA #$02\nSTA $8000\nLDA $8000\nBRK");
+    rom[0xC000..0xC000 + code.len()].copy_from_slice(&code);
+    rom[0xFFFC] = 0x00;
+    rom[0xFFFD] = 0xC0;
+    let mut cpu = CPU::new(BankedMemory::new(Uxrom::new(4), rom));
+    cpu.halt_conditions.brk = true;
+    cpu.reset();
+    cpu.run_for_instructions(10);
+    assert_eq!(cpu.a, 0x02);
+}
+
+#[test]
+fn bank_state_round_trips() {
+    let mut bus = BankedMemory::new(Uxrom::new(4), prg_rom(4));
+    bus.write(0x8000, 1);
+    let snapshot = bus.bank_state();
+    bus.write(0x8000, 2);
+    assert_eq!(bus.read(0x8000), 2);
+    bus.restore_bank_state(&snapshot).unwrap();
+    assert_eq!(bus.read(0x8000), 1);
+    assert_eq!(bus.bank_state(), snapshot);
+}
+
+#[test]
+fn restoring_a_state_of_the_wrong_size_fails() {
+    let mut bus = BankedMemory::new(Uxrom::new(4), prg_rom(4));
+    bus.write(0x8000, 1);
+    let error = BankStateError {
+        expected: 1,
+        found: 0,
+    };
+    assert_eq!(bus.restore_bank_state(&BankState::default()), Err(error));
+    let foreign = BankState {
+        registers: vec![2, 3],
+    };
+    assert_eq!(
+        bus.restore_bank_state(&foreign),
+        Err(BankStateError {
+            expected: 1,
+            found: 2
+        })
+    );
+    // The mapper is left as it was
+    assert_eq!(bus.read(0x8000), 1);
+
+    let mut sideways = SidewaysRom::new();
+    assert_eq!(
+        sideways.restore_bank_state(&BankState::default()),
+        Err(error)
+    );
+    let mut c64 = C64Banking::new();
+    assert_eq!(c64.restore_bank_state(&BankState::default()), Err(error));
+    assert_eq!(c64.bank_state().registers, [0x37]);
+}
+
+#[test]
+fn sideways_ram_slot_is_writable() {
+    let mut bus = BankedMemory::new(SidewaysRom::new().with_ram_slot(4), vec![0; 16 * 0x4000]);
+    bus.write(0xFE30, 0x03);
+    bus.write(0x8000, 0xAA);
+    assert_eq!(bus.read(0x8000), 0x00);
+
+    bus.write(0xFE30, 0x04);
+    bus.write(0x8000, 0xAA);
+    assert_eq!(bus.read(0x8000), 0xAA);
+    assert_eq!(bus.store[4 * 0x4000], 0xAA);
+    assert_eq!(bus.memory.read(0x8000), 0x00);
+}
+
+#[test]
+fn sideways_slots_past_the_store_are_empty() {
+    let mut bus = BankedMemory::new(SidewaysRom::new(), prg_rom(2));
+    bus.write(0xFE31, 0x01);
+    assert_eq!(bus.read(0x9000), 1);
+    bus.write(0xFE30, 0x0F);
+    assert_eq!(bus.read(0x9000), 0xFF);
+}
+
+#[test]
+fn c64_port_banks_rom_over_ram() {
+    let mut store = vec![0xBA; 0x2000];
+    store.extend(vec![0xEE; 0x2000]);
+    store.extend(vec![0xC0; 0x1000]);
+    let mut bus = BankedMemory::new(C64Banking::new(), store);
+
+    // Writes land in the RAM under the ROM
+    bus.write(0xA000, 0x11);
+    bus.write(0xE000, 0x22);
+    assert_eq!(bus.read(0xA000), 0xBA);
+    assert_eq!(bus.read(0xE000), 0xEE);
+    assert_eq!(bus.read(0xD000), 0x00);
+
+    // Character ROM in place of I/O
+    bus.write(0x0001, 0x33);
+    assert_eq!(bus.read(0xD000), 0xC0);
+
+    // BASIC out, KERNAL in
+    bus.write(0x0001, 0x36);
+    assert_eq!(bus.read(0xA000), 0x11);
+    assert_eq!(bus.read(0xE000), 0xEE);
+
+    // All RAM
+    bus.write(0x0001, 0x34);
+    assert_eq!(bus.read(0xA000), 0x11);
+    assert_eq!(bus.read(0xE000), 0x22);
+    assert_eq!(bus.read(0x0001), 0x34);
+}
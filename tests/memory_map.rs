@@ -0,0 +1,79 @@
+// Memory maps built from RAM, ROM, mirrored, unmapped and device regions.
+
+use rs6502::assembler::assemble;
+use rs6502::bus::Bus;
+use rs6502::cpu::CPU;
+use rs6502::memory::Memory;
+use rs6502::memory_map::{MemoryMap, RomWrites};
+
+// A NES-like layout: 2KB of RAM mirrored up to $1FFF, nothing until the
+// cartridge ROM at $8000
+fn console(source: &str) -> MemoryMap {
+    let mut map = MemoryMap::new();
+    map.add_ram(0x0000..=0x07FF);
+    map.add_mirror(0x0800..=0x1FFF, 0x0000..=0x07FF);
+    map.add_rom(0x8000..=0xFFFF, RomWrites::Log);
+    map.load_program(assemble(source), 0x8000);
+    map.load_program(vec![0x00, 0x80], 0xFFFC);
+    map
+}
+
+#[test]
+fn mirrors_reach_the_same_ram() {
+    let mut map = console("");
+    map.write(0x0812, 0xAB);
+    assert_eq!(map.read(0x0012), 0xAB);
+    assert_eq!(map.read(0x1012), 0xAB);
+    assert_eq!(map.read(0x1812), 0xAB);
+    map.write(0x1FFF, 0xCD);
+    assert_eq!(map.read(0x07FF), 0xCD);
+}
+
+#[test]
+fn rom_writes_are_dropped_and_logged() {
+    let mut cpu = CPU::new(console("LDA #$2A\nSTA $8000\nSTA $0200\nLDA $8000"));
+    cpu.reset();
+    cpu.run_for_instructions(4);
+    assert_eq!(cpu.a, 0xA9);
+    assert_eq!(cpu.memory.rom_writes(), [(0x8000, 0x2A)]);
+    assert_eq!(cpu.memory.memory().read(0x0200), 0x2A);
+
+    let mut map = console("");
+    map.add_rom(0xC000..=0xFFFF, RomWrites::Ignore);
+    map.write(0xC000, 0x01);
+    map.write(0x8000, 0x01);
+    assert_eq!(map.rom_writes(), [(0x8000, 0x01)]);
+    assert_eq!(map.read(0xC000), 0x00);
+}
+
+#[test]
+fn unmapped_reads_see_open_bus() {
+    let mut map = console("");
+    map.write(0x0010, 0x5A);
+    assert_eq!(map.read(0x0010), 0x5A);
+    assert_eq!(map.read(0x6000), 0x5A);
+    map.write(0x6000, 0x77);
+    assert_eq!(map.memory().read(0x6000), 0x00);
+    assert_eq!(map.read(0x5000), 0x77);
+}
+
+#[test]
+fn later_regions_take_precedence() {
+    let mut map = console("");
+    map.add_unmapped(0x0400..=0x04FF);
+    map.write(0x0400, 0x11);
+    assert_eq!(map.memory().read(0x0400), 0x00);
+    map.write(0x03FF, 0x22);
+    assert_eq!(map.read(0x0BFF), 0x22);
+}
+
+#[test]
+fn device_windows_see_relative_addresses() {
+    let mut map = console("");
+    map.add_device(0x2000..=0x3FFF, Memory::new());
+    map.add_mirror(0x2008..=0x3FFF, 0x2000..=0x2007);
+    map.write(0x2003, 0x44);
+    assert_eq!(map.read(0x200B), 0x44);
+    assert_eq!(map.read(0x3FFB), 0x44);
+    assert_eq!(map.memory().read(0x2003), 0x00);
+}
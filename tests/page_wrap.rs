@@ -0,0 +1,134 @@
+// Page boundary behaviour of the 6502 addressing modes: indexing and pointers
+// in zero page never leave it, and JMP (abs) on the NMOS part never leaves the
+// pointer's page.
+
+mod common;
+
+use common::cpu_for;
+use rs6502::cpu::{CPU, StepOutcome};
+use rs6502::variant::CpuVariant;
+
+fn run(cpu: &mut CPU, instructions: usize) {
+    for _ in 0..instructions {
+        cpu.execute_instruction().unwrap();
+    }
+}
+
+#[test]
+fn jmp_indirect_wraps_within_page_on_nmos() {
+    let mut cpu = cpu_for(CpuVariant::Nmos6502, "JMP ($10FF)");
+    cpu.memory.write(0x10FF, 0x34);
+    cpu.memory.write(0x1000, 0x12);
+    cpu.memory.write(0x1100, 0x56);
+    run(&mut cpu, 1);
+    assert_eq!(cpu.pc, 0x1234);
+}
+
+#[test]
+fn jmp_indirect_crosses_page_on_cmos() {
+    let mut cpu = cpu_for(CpuVariant::Cmos65C02, "JMP ($10FF)");
+    cpu.memory.write(0x10FF, 0x34);
+    cpu.memory.write(0x1000, 0x12);
+    cpu.memory.write(0x1100, 0x56);
+    run(&mut cpu, 1);
+    assert_eq!(cpu.pc, 0x5634);
+}
+
+#[test]
+fn jmp_indirect_wraps_when_ticked() {
+    let mut cpu = cpu_for(CpuVariant::Nmos6502, "JMP ($10FF)");
+    cpu.memory.write(0x10FF, 0x34);
+    cpu.memory.write(0x1000, 0x12);
+    cpu.memory.write(0x1100, 0x56);
+    let reads: Vec<u16> = (0..5).map(|_| cpu.tick().unwrap().address).collect();
+    assert_eq!(reads, [0x0600, 0x0601, 0x0602, 0x10FF, 0x1000]);
+    assert_eq!(cpu.pc, 0x1234);
+}
+
+#[test]
+fn jmp_indirect_within_page() {
+    let mut cpu = cpu_for(CpuVariant::Nmos6502, "JMP ($10FE)");
+    cpu.memory.write(0x10FE, 0x34);
+    cpu.memory.write(0x10FF, 0x12);
+    run(&mut cpu, 1);
+    assert_eq!(cpu.pc, 0x1234);
+}
+
+#[test]
+fn zero_page_x_wraps() {
+    let mut cpu = cpu_for(CpuVariant::Nmos6502, "LDX #$01\nLDA $FF,X\nSTA $FF,X");
+    cpu.memory.write(0x0000, 0xAA);
+    cpu.memory.write(0x0100, 0x55);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.a, 0xAA);
+
+    cpu.a = 0x77;
+    run(&mut cpu, 1);
+    assert_eq!(cpu.memory.read(0x0000), 0x77);
+    assert_eq!(cpu.memory.read(0x0100), 0x55);
+}
+
+#[test]
+fn zero_page_y_wraps() {
+    let mut cpu = cpu_for(CpuVariant::Nmos6502, "LDY #$02\nLDX $FF,Y\nSTX $FF,Y");
+    cpu.memory.write(0x0001, 0xAA);
+    cpu.memory.write(0x0101, 0x55);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.x, 0xAA);
+
+    cpu.x = 0x77;
+    run(&mut cpu, 1);
+    assert_eq!(cpu.memory.read(0x0001), 0x77);
+    assert_eq!(cpu.memory.read(0x0101), 0x55);
+}
+
+#[test]
+fn indexed_indirect_pointer_wraps() {
+    // $FE + X lands on $FF, so the pointer's high byte comes from $00
+    let mut cpu = cpu_for(CpuVariant::Nmos6502, "LDX #$01\nLDA ($FE,X)");
+    cpu.memory.write(0x00FF, 0x34);
+    cpu.memory.write(0x0000, 0x12);
+    cpu.memory.write(0x0100, 0x56);
+    cpu.memory.write(0x1234, 0xAA);
+    cpu.memory.write(0x5634, 0x55);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.a, 0xAA);
+}
+
+#[test]
+fn indexed_indirect_index_wraps() {
+    // $80 + X wraps to $00 rather than reaching $0100
+    let mut cpu = cpu_for(CpuVariant::Nmos6502, "LDX #$80\nLDA ($80,X)");
+    cpu.memory.write(0x0000, 0x34);
+    cpu.memory.write(0x0001, 0x12);
+    cpu.memory.write(0x0100, 0x78);
+    cpu.memory.write(0x0101, 0x56);
+    cpu.memory.write(0x1234, 0xAA);
+    cpu.memory.write(0x5678, 0x55);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.a, 0xAA);
+}
+
+#[test]
+fn indirect_indexed_pointer_wraps() {
+    let mut cpu = cpu_for(CpuVariant::Nmos6502, "LDY #$01\nLDA ($FF),Y");
+    cpu.memory.write(0x00FF, 0x33);
+    cpu.memory.write(0x0000, 0x12);
+    cpu.memory.write(0x0100, 0x56);
+    cpu.memory.write(0x1234, 0xAA);
+    cpu.memory.write(0x5634, 0x55);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.a, 0xAA);
+}
+
+#[test]
+fn indirect_indexed_carries_into_high_byte() {
+    // Only the pointer stays in page zero; adding Y may cross a page
+    let mut cpu = cpu_for(CpuVariant::Nmos6502, "LDY #$01\nLDA ($10),Y");
+    cpu.memory.write(0x0010, 0xFF);
+    cpu.memory.write(0x0011, 0x12);
+    cpu.memory.write(0x1300, 0xAA);
+    run(&mut cpu, 1);
+    assert_eq!(cpu.execute_instruction(), Ok(StepOutcome::Executed(6)));
+    assert_eq!(cpu.a, 0xAA);
+}
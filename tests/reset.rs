@@ -0,0 +1,90 @@
+// The RESET sequence and power-on state.
+
+use rs6502::cpu::{CPU, StatusFlags, StepOutcome};
+use rs6502::memory::Memory;
+use rs6502::variant::CpuVariant;
+
+fn memory_with_vector() -> Memory {
+    let mut memory = Memory::new();
+    memory.write_u16(0xFFFC, 0x0600);
+    memory
+}
+
+#[test]
+fn reset_from_power_on() {
+    let mut cpu = CPU::new(memory_with_vector());
+    cpu.reset();
+    assert_eq!(cpu.pc, 0x0600);
+    assert_eq!(cpu.sp, 0xFD);
+    assert_eq!(cpu.status.bits(), 0x24);
+    assert_eq!(cpu.cycles, 7);
+}
+
+#[test]
+fn reset_pushes_nothing_but_moves_sp() {
+    let mut memory = memory_with_vector();
+    memory.write(0x01F0, 0xAA);
+    memory.write(0x01EF, 0xBB);
+    memory.write(0x01EE, 0xCC);
+    let mut cpu = CPU::new(memory);
+    cpu.sp = 0xF0;
+    cpu.status = StatusFlags::from_bits(0x81);
+    cpu.reset();
+    assert_eq!(cpu.sp, 0xED);
+    assert_eq!(cpu.status.bits(), 0xA5);
+    assert_eq!(cpu.memory.read(0x01F0), 0xAA);
+    assert_eq!(cpu.memory.read(0x01EF), 0xBB);
+    assert_eq!(cpu.memory.read(0x01EE), 0xCC);
+}
+
+#[test]
+fn cmos_reset_clears_decimal() {
+    let mut cpu = CPU::with_variant(memory_with_vector(), CpuVariant::Cmos65C02);
+    cpu.status = StatusFlags::from_bits(0x08);
+    cpu.reset();
+    assert_eq!(cpu.status.bits(), 0x24);
+
+    let mut cpu = CPU::new(memory_with_vector());
+    cpu.status = StatusFlags::from_bits(0x08);
+    cpu.reset();
+    assert_eq!(cpu.status.bits(), 0x2C);
+}
+
+#[test]
+fn randomized_state_is_reproducible() {
+    let power_on = |seed| {
+        let mut memory = Memory::new();
+        memory.randomize(seed);
+        memory.write_u16(0xFFFC, 0x0600);
+        let mut cpu = CPU::new(memory);
+        cpu.randomize_registers(seed);
+        cpu.reset();
+        cpu
+    };
+    let first = power_on(1);
+    let again = power_on(1);
+    let other = power_on(2);
+    assert_eq!(first.memory, again.memory);
+    assert_eq!((first.a, first.x, first.sp), (again.a, again.x, again.sp));
+    assert_ne!(first.memory, other.memory);
+    assert!(
+        first
+            .status
+            .contains(StatusFlags::INTERRUPT_DISABLE | StatusFlags::UNUSED)
+    );
+    assert_eq!(first.pc, 0x0600);
+}
+
+#[test]
+fn uninitialized_read_sees_random_memory() {
+    // LDA $10 reads memory the program never wrote
+    let mut memory = Memory::new();
+    memory.randomize(7);
+    let garbage = memory.read(0x0010);
+    memory.load_program(vec![0xA5, 0x10], 0x0600);
+    memory.write_u16(0xFFFC, 0x0600);
+    let mut cpu = CPU::new(memory);
+    cpu.reset();
+    assert_eq!(cpu.execute_instruction(), Ok(StepOutcome::Executed(3)));
+    assert_eq!(cpu.a, garbage);
+}
@@ -0,0 +1,100 @@
+// Budgeted and conditional runs, and the summaries they return.
+
+mod common;
+
+use common::{cpu_for, cpu_with};
+use rs6502::cpu::{CpuError, HaltReason, RunSummary, StopReason};
+use rs6502::variant::CpuVariant;
+
+// A loop of 2 + 5 * (2 + 3) - 1 = 26 cycles in 11 instructions, then BRK
+const COUNTDOWN: &str = "LDX #$05\nDEX\nBNE #$FD\nBRK";
+
+#[test]
+fn run_for_instructions() {
+    let mut cpu = cpu_with(COUNTDOWN);
+    let summary = cpu.run_for_instructions(3);
+    assert_eq!(
+        summary,
+        RunSummary {
+            instructions: 3,
+            cycles: 7,
+            stop: StopReason::InstructionLimit,
+        }
+    );
+    assert_eq!(cpu.x, 0x04);
+}
+
+#[test]
+fn run_for_cycles_finishes_the_last_instruction() {
+    let mut cpu = cpu_with(COUNTDOWN);
+    let summary = cpu.run_for_cycles(6);
+    assert_eq!(summary.instructions, 3);
+    assert_eq!(summary.cycles, 7);
+    assert_eq!(summary.stop, StopReason::CycleLimit);
+
+    // Budgets count from the start of each call
+    assert_eq!(cpu.run_for_cycles(5).cycles, 5);
+}
+
+#[test]
+fn run_until_pc() {
+    let mut cpu = cpu_with(COUNTDOWN);
+    let summary = cpu.run_until_pc(0x0605);
+    assert_eq!(summary.instructions, 11);
+    assert_eq!(summary.cycles, 26);
+    assert_eq!(summary.stop, StopReason::Condition);
+    assert_eq!(cpu.pc, 0x0605);
+
+    // Already there, so nothing runs
+    assert_eq!(cpu.run_until_pc(0x0605).instructions, 0);
+}
+
+#[test]
+fn run_until_condition() {
+    let mut cpu = cpu_with(COUNTDOWN);
+    let summary = cpu.run_until(|cpu| cpu.x == 0x02);
+    assert_eq!(summary.stop, StopReason::Condition);
+    assert_eq!(summary.instructions, 6);
+}
+
+#[test]
+fn run_stops_on_halt() {
+    let mut cpu = cpu_with(COUNTDOWN);
+    cpu.halt_conditions.brk = true;
+    let summary = cpu.run_for_instructions(100);
+    // The BRK that halts counts as well
+    assert_eq!(summary.instructions, 12);
+    assert_eq!(
+        summary.stop,
+        StopReason::Halted(CpuError::Halted(HaltReason::Brk))
+    );
+
+    // Nothing more runs once halted
+    let summary = cpu.run_for_instructions(100);
+    assert_eq!((summary.instructions, summary.cycles), (0, 0));
+}
+
+#[test]
+fn run_stops_while_waiting_for_an_interrupt() {
+    let mut cpu = cpu_for(CpuVariant::Cmos65C02, "CLI\nWAI\nNOP");
+    cpu.memory.write_u16(0xFFFE, 0x0700);
+    let summary = cpu.run_for_instructions(100);
+    assert_eq!(
+        summary,
+        RunSummary {
+            instructions: 2,
+            cycles: 5,
+            stop: StopReason::Waiting,
+        }
+    );
+    assert_eq!(cpu.run_until_pc(0x0700).stop, StopReason::Waiting);
+    assert_eq!(cpu.run_for_instructions(1).cycles, 0);
+
+    // A cycle budget idles through it
+    let summary = cpu.run_for_cycles(10);
+    assert_eq!(summary.stop, StopReason::CycleLimit);
+    assert_eq!((summary.instructions, summary.cycles), (0, 10));
+
+    cpu.irq();
+    assert_eq!(cpu.run_until_pc(0x0700).stop, StopReason::Condition);
+}
@@ -0,0 +1,59 @@
+// The status register type, and how bits 4 and 5 look on the stack.
+
+mod common;
+
+use common::cpu_with;
+use rs6502::cpu::StatusFlags;
+
+#[test]
+fn display_names_the_flags() {
+    assert_eq!(StatusFlags::empty().to_string(), "nv-bdizc");
+    assert_eq!(StatusFlags::from_bits(0xFF).to_string(), "NV-BDIZC");
+    let flags = StatusFlags::NEGATIVE | StatusFlags::INTERRUPT_DISABLE | StatusFlags::CARRY;
+    assert_eq!(flags.to_string(), "Nv-bdIzC");
+}
+
+#[test]
+fn set_insert_and_remove() {
+    let mut flags = StatusFlags::empty();
+    flags.insert(StatusFlags::CARRY | StatusFlags::ZERO);
+    assert!(flags.contains(StatusFlags::CARRY));
+    assert!(flags.contains(StatusFlags::CARRY | StatusFlags::ZERO));
+    flags.remove(StatusFlags::ZERO);
+    assert!(!flags.contains(StatusFlags::CARRY | StatusFlags::ZERO));
+    flags.set(StatusFlags::OVERFLOW, true);
+    assert_eq!(flags.bits(), 0x41);
+    flags.set_zero_and_negative(0x80);
+    assert_eq!(flags.bits(), 0xC1);
+}
+
+#[test]
+fn pushed_and_pulled_bytes() {
+    let flags = StatusFlags::CARRY;
+    assert_eq!(flags.pushed_by_instruction(), 0x31);
+    assert_eq!(flags.pushed_by_interrupt(), 0x21);
+    assert_eq!(StatusFlags::pulled(0xFF).bits(), 0xEF);
+    assert_eq!(StatusFlags::pulled(0x00).bits(), 0x20);
+}
+
+#[test]
+fn php_pushes_b_and_plp_drops_it() {
+    let mut cpu = cpu_with("SEC\nPHP\nLDA #$FF\nPHA\nPLP");
+    for _ in 0..5 {
+        cpu.execute_instruction().unwrap();
+    }
+    assert_eq!(cpu.memory.read(0x01FD), 0x25 | 0x10);
+    assert_eq!(cpu.status.bits(), 0xEF);
+}
+
+#[test]
+fn irq_pushes_b_clear() {
+    let mut cpu = cpu_with("CLI\nNOP");
+    cpu.memory.write_u16(0xFFFE, 0x0700);
+    cpu.execute_instruction().unwrap();
+    cpu.set_irq_line(true);
+    cpu.execute_instruction().unwrap();
+    assert_eq!(cpu.pc, 0x0700);
+    assert_eq!(cpu.memory.read(0x01FB), 0x20);
+    assert!(cpu.status.contains(StatusFlags::INTERRUPT_DISABLE));
+}
@@ -0,0 +1,251 @@
+// What each group of undocumented NMOS opcodes does to the registers, the
+// flags and memory, including ARR in decimal mode and the magic constant
+// that XAA and LAX #imm OR into A.
+
+mod common;
+
+use common::cpu_for;
+use rs6502::cpu::{CPU, StatusFlags};
+use rs6502::variant::CpuVariant;
+
+fn nmos(source: &str) -> CPU {
+    cpu_for(CpuVariant::Nmos6502Undocumented, source)
+}
+
+fn run(cpu: &mut CPU, instructions: usize) {
+    for _ in 0..instructions {
+        cpu.execute_instruction().unwrap();
+    }
+}
+
+fn flag(cpu: &CPU, flag: StatusFlags) -> bool {
+    cpu.status.contains(flag)
+}
+
+#[test]
+fn lax_loads_a_and_x() {
+    let mut cpu = nmos("LAX $10");
+    cpu.memory.write(0x0010, 0x80);
+    run(&mut cpu, 1);
+    assert_eq!((cpu.a, cpu.x), (0x80, 0x80));
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+}
+
+#[test]
+fn sax_stores_a_and_x_without_touching_flags() {
+    let mut cpu = nmos("LDA #$F0\nLDX #$3C\nSAX $10");
+    run(&mut cpu, 2);
+    let status = cpu.status;
+    run(&mut cpu, 1);
+    assert_eq!(cpu.memory.read(0x0010), 0x30);
+    assert_eq!((cpu.a, cpu.x), (0xF0, 0x3C));
+    assert_eq!(cpu.status, status);
+}
+
+#[test]
+fn dcp_decrements_then_compares() {
+    let mut cpu = nmos("LDA #$42\nDCP $10");
+    cpu.memory.write(0x0010, 0x43);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.memory.read(0x0010), 0x42);
+    assert_eq!(cpu.a, 0x42);
+    assert!(flag(&cpu, StatusFlags::ZERO));
+    assert!(flag(&cpu, StatusFlags::CARRY));
+}
+
+#[test]
+fn isc_increments_then_subtracts() {
+    let mut cpu = nmos("SEC\nLDA #$10\nISC $10");
+    cpu.memory.write(0x0010, 0x04);
+    run(&mut cpu, 3);
+    assert_eq!(cpu.memory.read(0x0010), 0x05);
+    assert_eq!(cpu.a, 0x0B);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+}
+
+#[test]
+fn slo_shifts_left_then_ors() {
+    let mut cpu = nmos("LDA #$01\nSLO $10");
+    cpu.memory.write(0x0010, 0x81);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.memory.read(0x0010), 0x02);
+    assert_eq!(cpu.a, 0x03);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+}
+
+#[test]
+fn rla_rotates_left_then_ands() {
+    let mut cpu = nmos("SEC\nLDA #$03\nRLA $10");
+    cpu.memory.write(0x0010, 0x81);
+    run(&mut cpu, 3);
+    assert_eq!(cpu.memory.read(0x0010), 0x03);
+    assert_eq!(cpu.a, 0x03);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+}
+
+#[test]
+fn sre_shifts_right_then_eors() {
+    let mut cpu = nmos("LDA #$FF\nSRE $10");
+    cpu.memory.write(0x0010, 0x03);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.memory.read(0x0010), 0x01);
+    assert_eq!(cpu.a, 0xFE);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+}
+
+#[test]
+fn rra_rotates_right_then_adds_with_the_carry_out() {
+    let mut cpu = nmos("CLC\nLDA #$10\nRRA $10");
+    cpu.memory.write(0x0010, 0x03);
+    run(&mut cpu, 3);
+    assert_eq!(cpu.memory.read(0x0010), 0x01);
+    // $10 + $01 + the bit rotated out
+    assert_eq!(cpu.a, 0x12);
+    assert!(!flag(&cpu, StatusFlags::CARRY));
+}
+
+#[test]
+fn anc_copies_n_into_c() {
+    let mut cpu = nmos("LDA #$FF\nANC #$80");
+    run(&mut cpu, 2);
+    assert_eq!(cpu.a, 0x80);
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+    assert!(flag(&cpu, StatusFlags::CARRY));
+}
+
+#[test]
+fn alr_ands_then_shifts_right() {
+    let mut cpu = nmos("LDA #$FF\nALR #$03");
+    run(&mut cpu, 2);
+    assert_eq!(cpu.a, 0x01);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+}
+
+#[test]
+fn arr_takes_c_and_v_from_bits_6_and_5() {
+    let mut cpu = nmos("LDA #$FF\nARR #$80");
+    run(&mut cpu, 2);
+    assert_eq!(cpu.a, 0x40);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+    assert!(flag(&cpu, StatusFlags::OVERFLOW));
+
+    // The carry rotates into bit 7
+    let mut cpu = nmos("SEC\nLDA #$FF\nARR #$FF");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.a, 0xFF);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+    assert!(!flag(&cpu, StatusFlags::OVERFLOW));
+}
+
+#[test]
+fn arr_corrects_each_digit_in_decimal_mode() {
+    // N and Z come from the rotated value, before either correction
+    let mut cpu = nmos("SED\nLDA #$FF\nARR #$FF");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.a, 0xD5);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+    assert!(!flag(&cpu, StatusFlags::NEGATIVE));
+    assert!(!flag(&cpu, StatusFlags::OVERFLOW));
+
+    // Only the low digit needs correcting
+    let mut cpu = nmos("SED\nLDA #$0F\nARR #$0F");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.a, 0x0D);
+    assert!(!flag(&cpu, StatusFlags::CARRY));
+
+    // The 2A03 has no decimal mode to correct for
+    let mut cpu = cpu_for(CpuVariant::Ricoh2A03, "SED\nLDA #$FF\nARR #$FF");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.a, 0x7F);
+}
+
+#[test]
+fn axs_subtracts_from_a_and_x_into_x() {
+    let mut cpu = nmos("LDA #$0F\nLDX #$FC\nAXS #$02");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.x, 0x0A);
+    assert_eq!(cpu.a, 0x0F);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+
+    // Borrowing clears C, and the carry in is ignored
+    let mut cpu = nmos("CLC\nLDA #$0F\nLDX #$FC\nAXS #$0D");
+    run(&mut cpu, 4);
+    assert_eq!(cpu.x, 0xFF);
+    assert!(!flag(&cpu, StatusFlags::CARRY));
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+}
+
+#[test]
+fn shx_and_shy_store_the_register_anded_with_the_high_byte_plus_one() {
+    let mut cpu = nmos("LDX #$FF\nLDY #$01\nSHX $1200,Y");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.memory.read(0x1201), 0x13);
+
+    let mut cpu = nmos("LDY #$FF\nLDX #$01\nSHY $1200,X");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.memory.read(0x1201), 0x13);
+}
+
+#[test]
+fn shx_across_a_page_writes_to_the_stored_value_page() {
+    // $0F & $13 is $03, which also becomes the high byte of $1301
+    let mut cpu = nmos("LDX #$0F\nLDY #$02\nSHX $12FF,Y");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.memory.read(0x0301), 0x03);
+    assert_eq!(cpu.memory.read(0x1301), 0x00);
+}
+
+#[test]
+fn tas_sets_sp_to_a_and_x_then_stores_it() {
+    let mut cpu = nmos("LDA #$F3\nLDX #$3F\nLDY #$01\nTAS $1200,Y");
+    run(&mut cpu, 4);
+    assert_eq!(cpu.sp, 0x33);
+    assert_eq!(cpu.memory.read(0x1201), 0x13);
+}
+
+#[test]
+fn las_loads_memory_and_sp_into_a_x_and_sp() {
+    let mut cpu = nmos("LDY #$01\nLAS $1200,Y");
+    cpu.memory.write(0x1201, 0xF0);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.sp, 0xFD & 0xF0);
+    assert_eq!((cpu.a, cpu.x), (0xF0, 0xF0));
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+}
+
+#[test]
+fn xaa_ors_a_with_the_magic_constant() {
+    let mut cpu = nmos("LDA #$00\nLDX #$FF\nXAA #$FF");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.a, 0xEE);
+
+    let mut cpu = nmos("LDA #$00\nLDX #$0F\nXAA #$FF");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.a, 0x0E);
+
+    let mut cpu = cpu_for(CpuVariant::Ricoh2A03, "LDA #$00\nLDX #$FF\nXAA #$FF");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.a, 0xFF);
+}
+
+#[test]
+fn lax_immediate_ors_a_with_the_magic_constant() {
+    let mut cpu = nmos("LDA #$00\nLAX #$FF");
+    run(&mut cpu, 2);
+    assert_eq!((cpu.a, cpu.x), (0xEE, 0xEE));
+
+    let mut cpu = nmos("LDA #$00\nLAX #$F1");
+    run(&mut cpu, 2);
+    assert_eq!((cpu.a, cpu.x), (0xE0, 0xE0));
+
+    let mut cpu = cpu_for(CpuVariant::Ricoh2A03, "LDA #$00\nLAX #$FF");
+    run(&mut cpu, 2);
+    assert_eq!((cpu.a, cpu.x), (0xFF, 0xFF));
+}
+
+#[test]
+#[should_panic(expected = "LAX is not available on Nmos6502")]
+fn lax_does_not_assemble_for_the_documented_set() {
+    cpu_for(CpuVariant::Nmos6502, "LAX $20");
+}
@@ -0,0 +1,330 @@
+// The 65C816: switching between emulation and native mode, register widths,
+// 16-bit arithmetic, direct page and long addressing, block moves and the
+// native mode interrupt sequence.
+
+mod common;
+
+use common::{START, cpu_for};
+use rs6502::assembler::assemble_for;
+use rs6502::cpu::{CPU, StatusFlags, StepOutcome};
+use rs6502::memory::Memory;
+use rs6502::variant::CpuVariant;
+
+const VARIANT: CpuVariant = CpuVariant::W65C816;
+
+fn emulation(source: &str) -> CPU {
+    cpu_for(VARIANT, source)
+}
+
+// Switches to native mode with CLC, XCE before running `source`
+fn native(source: &str) -> CPU {
+    let mut cpu = emulation(&format!("CLC\nXCE\n{}", source));
+    run(&mut cpu, 2);
+    cpu
+}
+
+// Like `native`, with two 64KB banks of memory
+fn native_with_banks(source: &str) -> CPU {
+    let mut memory = Memory::with_size(0x2_0000);
+    let program = assemble_for(&format!("CLC\nXCE\n{}", source), VARIANT);
+    memory.load_program(program, START);
+    memory.write_u16(0xFFFC, START);
+    let mut cpu = CPU::with_variant(memory, VARIANT);
+    cpu.reset();
+    run(&mut cpu, 2);
+    cpu
+}
+
+fn run(cpu: &mut CPU, instructions: usize) {
+    for _ in 0..instructions {
+        cpu.execute_instruction().unwrap();
+    }
+}
+
+fn flag(cpu: &CPU, flag: StatusFlags) -> bool {
+    cpu.status.contains(flag)
+}
+
+#[test]
+fn xce_swaps_carry_and_emulation() {
+    let mut cpu = emulation("CLC\nXCE\nSEC\nXCE");
+    assert!(cpu.regs816.emulation);
+
+    run(&mut cpu, 2);
+    assert!(!cpu.regs816.emulation);
+    // Carry gets the old E, and both registers start out 8 bits wide
+    assert!(flag(&cpu, StatusFlags::CARRY));
+    assert!(flag(&cpu, StatusFlags::ACCUMULATOR_8BIT));
+    assert!(flag(&cpu, StatusFlags::INDEX_8BIT));
+
+    run(&mut cpu, 2);
+    assert!(cpu.regs816.emulation);
+    assert!(!flag(&cpu, StatusFlags::CARRY));
+}
+
+#[test]
+fn returning_to_emulation_narrows_the_registers() {
+    let mut cpu = native("REP #$30\nLDX #$1234\nLDA #$01FF\nTCS\nSEC\nXCE");
+    run(&mut cpu, 4);
+    assert_eq!(cpu.index_x(), 0x1234);
+    assert_eq!(cpu.stack_pointer(), 0x01FF);
+
+    run(&mut cpu, 2);
+    assert_eq!(cpu.index_x(), 0x0034);
+    assert_eq!(cpu.stack_pointer(), 0x01FF);
+    // B is kept, as it is swapped rather than cleared
+    assert_eq!(cpu.accumulator(), 0x01FF);
+}
+
+#[test]
+fn bit_4_reads_clear_in_emulation_mode() {
+    // After reset, after a round trip through native mode, and after PLP
+    let mut cpu = emulation("CLC\nXCE\nSEC\nXCE\nPHP\nPLP");
+    assert!(!flag(&cpu, StatusFlags::BREAK));
+    assert!(flag(&cpu, StatusFlags::UNUSED));
+    let reset = cpu.status;
+
+    run(&mut cpu, 4);
+    assert!(!flag(&cpu, StatusFlags::BREAK));
+    assert_eq!(cpu.status.bits() & 0x30, reset.bits() & 0x30);
+
+    // PHP still pushes it set
+    run(&mut cpu, 1);
+    assert_eq!(cpu.memory.read(0x01FD) & 0x30, 0x30);
+    run(&mut cpu, 1);
+    assert!(!flag(&cpu, StatusFlags::BREAK));
+}
+
+#[test]
+fn rep_and_sep_change_the_register_widths() {
+    let mut cpu = native("REP #$30\nLDA #$1234\nLDX #$ABCD\nSEP #$10\nSEP #$20\nLDA #$FF");
+    run(&mut cpu, 3);
+    assert_eq!(cpu.accumulator(), 0x1234);
+    assert_eq!(cpu.index_x(), 0xABCD);
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+
+    // Narrowing the index registers clears their high bytes
+    run(&mut cpu, 1);
+    assert_eq!(cpu.index_x(), 0x00CD);
+
+    // Narrowing the accumulator keeps B
+    run(&mut cpu, 2);
+    assert_eq!(cpu.accumulator(), 0x12FF);
+}
+
+#[test]
+fn rep_cannot_widen_registers_in_emulation_mode() {
+    let mut cpu = emulation("REP #$30\nLDA #$12");
+    run(&mut cpu, 2);
+    assert!(flag(&cpu, StatusFlags::ACCUMULATOR_8BIT));
+    assert!(!flag(&cpu, StatusFlags::INDEX_8BIT));
+    assert_eq!(cpu.pc, 0x0604);
+    assert_eq!(cpu.accumulator(), 0x0012);
+}
+
+#[test]
+fn wide_adc_carries_across_both_bytes() {
+    let mut cpu = native("REP #$20\nCLC\nLDA #$12FF\nADC #$0001\nADC #$ED00");
+    run(&mut cpu, 4);
+    assert_eq!(cpu.accumulator(), 0x1300);
+    assert!(!flag(&cpu, StatusFlags::CARRY));
+    run(&mut cpu, 1);
+    assert_eq!(cpu.accumulator(), 0x0000);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+    assert!(flag(&cpu, StatusFlags::ZERO));
+
+    let mut cpu = native("REP #$20\nCLC\nLDA #$7FFF\nADC #$0001");
+    run(&mut cpu, 4);
+    assert_eq!(cpu.accumulator(), 0x8000);
+    assert!(flag(&cpu, StatusFlags::OVERFLOW));
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+}
+
+#[test]
+fn wide_sbc_borrows_across_both_bytes() {
+    let mut cpu = native("REP #$20\nSEC\nLDA #$1000\nSBC #$0001\nLDA #$0000\nSBC #$0001");
+    run(&mut cpu, 4);
+    assert_eq!(cpu.accumulator(), 0x0FFF);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+    run(&mut cpu, 2);
+    assert_eq!(cpu.accumulator(), 0xFFFF);
+    assert!(!flag(&cpu, StatusFlags::CARRY));
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+}
+
+#[test]
+fn wide_decimal_arithmetic_corrects_four_digits() {
+    let mut cpu = native("REP #$20\nSED\nCLC\nLDA #$1999\nADC #$0001\nADC #$8000");
+    run(&mut cpu, 5);
+    assert_eq!(cpu.accumulator(), 0x2000);
+    run(&mut cpu, 1);
+    // 2000 + 8000 carries out of the fourth digit
+    assert_eq!(cpu.accumulator(), 0x0000);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+    assert!(flag(&cpu, StatusFlags::ZERO));
+
+    let mut cpu = native("REP #$20\nSED\nSEC\nLDA #$2000\nSBC #$0001\nLDA #$0000\nSBC #$0001");
+    run(&mut cpu, 5);
+    assert_eq!(cpu.accumulator(), 0x1999);
+    assert!(flag(&cpu, StatusFlags::CARRY));
+    run(&mut cpu, 2);
+    assert_eq!(cpu.accumulator(), 0x9999);
+    assert!(!flag(&cpu, StatusFlags::CARRY));
+    assert!(flag(&cpu, StatusFlags::NEGATIVE));
+}
+
+#[test]
+fn direct_page_is_relative_to_d() {
+    let mut cpu = native("REP #$20\nLDA #$1200\nTCD\nSEP #$20\nLDX #$05\nLDA $10\nLDY $10,X");
+    cpu.memory.write(0x1210, 0x42);
+    cpu.memory.write(0x1215, 0x43);
+    run(&mut cpu, 7);
+    assert_eq!(cpu.regs816.d, 0x1200);
+    assert_eq!(cpu.a, 0x42);
+    assert_eq!(cpu.y, 0x43);
+}
+
+#[test]
+fn long_addresses_reach_other_banks() {
+    let mut cpu = native_with_banks("LDA $012345\nLDA #$99\nSTA $01FFFF");
+    cpu.memory.write_long(0x01_2345, 0x42);
+    cpu.memory.write(0x2345, 0xFF);
+    run(&mut cpu, 1);
+    assert_eq!(cpu.a, 0x42);
+    run(&mut cpu, 2);
+    assert_eq!(cpu.memory.read_long(0x01_FFFF), 0x99);
+    assert_eq!(cpu.memory.read(0xFFFF), 0x00);
+}
+
+#[test]
+fn long_indirect_reads_a_three_byte_pointer() {
+    let mut cpu = native_with_banks("LDY #$02\nLDA [$10]\nTAX\nLDA [$10],Y");
+    cpu.memory.write_u16(0x0010, 0x1234);
+    cpu.memory.write(0x0012, 0x01);
+    cpu.memory.write_long(0x01_1234, 0x42);
+    cpu.memory.write_long(0x01_1236, 0x43);
+    run(&mut cpu, 4);
+    assert_eq!((cpu.x, cpu.a), (0x42, 0x43));
+}
+
+#[test]
+fn jsl_and_rtl_cross_banks() {
+    let mut cpu = native_with_banks("JSL $018000\nNOP");
+    cpu.memory.write_long(0x01_8000, 0x6B); // RTL
+    run(&mut cpu, 1);
+    assert_eq!((cpu.regs816.pbr, cpu.pc), (0x01, 0x8000));
+    run(&mut cpu, 1);
+    assert_eq!((cpu.regs816.pbr, cpu.pc), (0x00, 0x0606));
+}
+
+#[test]
+fn mvn_copies_upwards_one_byte_per_step() {
+    let mut cpu =
+        native_with_banks("REP #$30\nLDA #$0002\nLDX #$1000\nLDY #$2000\nMVN $01,$00\nNOP");
+    for (offset, value) in [0x11, 0x22, 0x33].into_iter().enumerate() {
+        cpu.memory.write_long(0x01_1000 + offset as u32, value);
+    }
+    run(&mut cpu, 4);
+    let mvn = cpu.pc;
+
+    // The count is C + 1 bytes, and PC stays on MVN until they are moved
+    run(&mut cpu, 2);
+    assert_eq!(cpu.pc, mvn);
+    run(&mut cpu, 1);
+    assert_eq!(cpu.pc, mvn + 3);
+
+    assert_eq!(
+        [0x2000, 0x2001, 0x2002].map(|address| cpu.memory.read(address)),
+        [0x11, 0x22, 0x33]
+    );
+    assert_eq!(cpu.accumulator(), 0xFFFF);
+    assert_eq!((cpu.index_x(), cpu.index_y()), (0x1003, 0x2003));
+    assert_eq!(cpu.regs816.dbr, 0x00);
+}
+
+#[test]
+fn mvp_copies_downwards() {
+    let mut cpu = native_with_banks("REP #$30\nLDA #$0002\nLDX #$1002\nLDY #$2002\nMVP $00,$01");
+    for (offset, value) in [0x11, 0x22, 0x33].into_iter().enumerate() {
+        cpu.memory.write(0x1000 + offset as u16, value);
+    }
+    run(&mut cpu, 7);
+    for (offset, value) in [0x11, 0x22, 0x33].into_iter().enumerate() {
+        assert_eq!(cpu.memory.read_long(0x01_2000 + offset as u32), value);
+    }
+    assert_eq!((cpu.index_x(), cpu.index_y()), (0x0FFF, 0x1FFF));
+    assert_eq!(cpu.regs816.dbr, 0x01);
+}
+
+// Native mode handlers for IRQ, NMI, BRK and COP, each a bare RTI
+fn native_with_handlers(source: &str) -> CPU {
+    let mut cpu = native(source);
+    for (vector, handler) in [
+        (0xFFEE, 0x0700),
+        (0xFFEA, 0x0710),
+        (0xFFE6, 0x0720),
+        (0xFFE4, 0x0730),
+    ] {
+        cpu.memory.write_u16(vector, handler);
+        cpu.memory.write(handler, 0x40);
+    }
+    cpu
+}
+
+#[test]
+fn native_irq_pushes_pbr_and_the_raw_status() {
+    let mut cpu = native_with_handlers("CLI\nNOP");
+    run(&mut cpu, 1);
+    cpu.irq();
+    assert_eq!(cpu.execute_instruction(), Ok(StepOutcome::Interrupted(8)));
+    assert_eq!(cpu.pc, 0x0700);
+    assert_eq!(cpu.stack_pointer(), 0x01F9);
+    assert_eq!(cpu.memory.read(0x01FD), 0x00); // PBR
+    assert_eq!(cpu.memory.read_u16(0x01FB), 0x0603);
+    // M and X as they were, with C left by XCE; there is no B to set
+    assert_eq!(cpu.memory.read(0x01FA), 0x31);
+    assert!(flag(&cpu, StatusFlags::INTERRUPT_DISABLE));
+
+    // RTI pulls PBR back as well
+    run(&mut cpu, 1);
+    assert_eq!(cpu.pc, 0x0603);
+    assert_eq!(cpu.stack_pointer(), 0x01FD);
+}
+
+#[test]
+fn native_mode_has_its_own_vectors() {
+    let mut cpu = native_with_handlers("NOP");
+    cpu.nmi();
+    run(&mut cpu, 1);
+    assert_eq!(cpu.pc, 0x0710);
+
+    // BRK no longer shares the IRQ vector
+    let mut cpu = native_with_handlers("BRK");
+    assert_eq!(cpu.execute_instruction(), Ok(StepOutcome::Executed(8)));
+    assert_eq!(cpu.pc, 0x0720);
+    assert_eq!(cpu.memory.read_u16(0x01FB), 0x0604);
+    // I is still set from reset
+    assert_eq!(cpu.memory.read(0x01FA), 0x35);
+
+    let mut cpu = native_with_handlers("COP #$00");
+    run(&mut cpu, 1);
+    assert_eq!(cpu.pc, 0x0730);
+}
+
+#[test]
+fn emulation_mode_uses_the_6502_vectors() {
+    let mut cpu = emulation("BRK\nNOP\nCOP #$00");
+    cpu.memory.write_u16(0xFFFE, 0x0700);
+    cpu.memory.write_u16(0xFFF4, 0x0730);
+    cpu.memory.write(0x0700, 0x40);
+    assert_eq!(cpu.execute_instruction(), Ok(StepOutcome::Executed(7)));
+    assert_eq!(cpu.pc, 0x0700);
+    // Three bytes pushed, with B set on the copy
+    assert_eq!(cpu.stack_pointer(), 0x01FA);
+    assert_eq!(cpu.memory.read(0x01FB) & 0x10, 0x10);
+
+    run(&mut cpu, 1);
+    assert_eq!(cpu.pc, 0x0602);
+    run(&mut cpu, 1);
+    assert_eq!(cpu.pc, 0x0730);
+}